@@ -0,0 +1,155 @@
+//! Serde helpers for the arbitrary-precision numbers of edn
+//!
+//! `num_bigint::BigInt` and `bigdecimal::BigDecimal` have no representation in
+//! the serde data model, so fields of those types name one of these modules:
+//!
+//! ```
+//! use bigdecimal::BigDecimal;
+//! use num_bigint::BigInt;
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Payment {
+//!     #[serde(with = "serde_edn::bignum::big_decimal")]
+//!     amount: BigDecimal,
+//!     #[serde(with = "serde_edn::bignum::big_int")]
+//!     id: BigInt,
+//! }
+//!
+//! let p: Payment = serde_edn::from_str("{:amount 1.10M :id 123N}").unwrap();
+//! assert_eq!(serde_edn::to_string(&p).unwrap(), "{:amount 1.10M :id 123N}");
+//! ```
+//!
+//! With serde_edn these read and write `N` integers and `M` decimals, plain
+//! integers and floats being accepted as well. Other formats see the number as
+//! a string of digits.
+//!
+//! With the feature enabled, plain integers too large for both i128 and u128
+//! are read as big integers, as in Clojure.
+
+use serde::de::{self, Deserializer, Unexpected, Visitor};
+use serde::ser::Serializer;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use crate::value::{BIGDEC_TOKEN, BIGINT_TOKEN};
+
+/// `N`-suffixed integers as `num_bigint::BigInt`
+pub mod big_int {
+    use super::*;
+    use num_bigint::BigInt;
+
+    pub fn serialize<S>(value: &BigInt, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(BIGINT_TOKEN, &value.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<BigInt, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(BIGINT_TOKEN, DigitsVisitor::new("an integer"))
+    }
+}
+
+/// `M`-suffixed exact decimals as `bigdecimal::BigDecimal`
+pub mod big_decimal {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    pub fn serialize<S>(value: &BigDecimal, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(BIGDEC_TOKEN, &value.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<BigDecimal, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(BIGDEC_TOKEN, DigitsVisitor::new("a decimal"))
+    }
+}
+
+/// Parses the number from its digits, whichever way they arrive
+struct DigitsVisitor<T> {
+    expecting: &'static str,
+    output: PhantomData<T>,
+}
+
+impl<T> DigitsVisitor<T> {
+    fn new(expecting: &'static str) -> Self {
+        DigitsVisitor {
+            expecting,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<'de, T> Visitor<'de> for DigitsVisitor<T>
+where
+    T: FromStr,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        v.to_string()
+            .parse()
+            .map_err(|_| de::Error::invalid_type(Unexpected::Float(v), &self))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        v.parse()
+            .map_err(|_| de::Error::invalid_value(Unexpected::Str(v), &self))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
@@ -0,0 +1,71 @@
+//! Serde helpers writing sequences as edn sets and lists
+//!
+//! serde hands every sequence to the serializer the same way, so a `Vec`, a
+//! `BTreeSet` and a `LinkedList` are all written as vectors. Fields name one of
+//! these modules to be written as `#{...}` or `(...)` instead:
+//!
+//! ```
+//! use serde_derive::{Deserialize, Serialize};
+//! use std::collections::BTreeSet;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Query {
+//!     #[serde(with = "serde_edn::set")]
+//!     tags: BTreeSet<String>,
+//!     #[serde(with = "serde_edn::list")]
+//!     path: Vec<u32>,
+//! }
+//!
+//! let q: Query = serde_edn::from_str(r#"{:tags #{"a" "b"} :path (1 2)}"#).unwrap();
+//! assert_eq!(serde_edn::to_string(&q).unwrap(), r#"{:tags #{"a" "b"} :path (1 2)}"#);
+//! ```
+//!
+//! Reading accepts any sequence, as it does without the helpers. Other formats
+//! see the plain sequence.
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use crate::value::{LIST_TOKEN, SET_TOKEN};
+
+/// Sequences written as `#{...}`, whose elements must be distinct
+pub mod set {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(SET_TOKEN, value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+/// Sequences written as `(...)`
+pub mod list {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(LIST_TOKEN, value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
@@ -1,156 +1,992 @@
-#![allow(warnings)]
-
-use serde::de::DeserializeOwned;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
 use serde::de::{
-    self, Deserialize, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
-    VariantAccess, Visitor,
+    self, Deserialize, DeserializeOwned, DeserializeSeed, EnumAccess, Expected, IntoDeserializer,
+    MapAccess, SeqAccess, Unexpected, Visitor,
 };
-use std::collections::BTreeMap;
 
-use edn::parser::Parser;
-use edn::Value as EValue;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::io;
+use std::marker::PhantomData;
 
-use crate::error::Error;
-use crate::Value;
+use crate::error::{Error, ErrorCode};
+use crate::read::{IoRead, Position, Read, Reference, SliceRead, StrRead};
+use crate::readers::TagReaders;
+#[cfg(feature = "bignum")]
+use crate::value::{visit_private_map, BIGDEC_TOKEN, BIGINT_TOKEN};
+use crate::value::{Symbol, Value, BYTES_TAG, VALUE_TOKEN};
 
-use std::ops::{AddAssign, MulAssign, Neg};
+#[cfg(feature = "bignum")]
+use bigdecimal::BigDecimal;
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
 
 type Result<T> = std::result::Result<T, Error>;
 
-pub struct Deserializer<'a> {
-    parser: Parser<'a>,
-    hack_val: Option<EValue>,
+/// A structure that deserializes edn into Rust values
+pub struct Deserializer<'de, R> {
+    read: R,
+    // holds bytes that cannot be borrowed from the input, e.g. strings with escapes
+    scratch: Vec<u8>,
+    // where the last token read starts, for pointing errors at it
+    token_start: Position,
+    // a token read ahead of time, e.g. to check for `nil` or the end of a collection
+    peeked: Option<Token<'de>>,
+    // the tag of base64 byte arrays
+    bytes_tag: Cow<'static, str>,
+    // what tagged literals other than the built-in ones are read into
+    tag_readers: TagReaders,
 }
 
-impl<'de> Deserializer<'de> {
-    pub fn from_str(input: &'de str) -> Self {
+impl<'de, R> Deserializer<'de, R>
+where
+    R: Read<'de>,
+{
+    /// Create a deserializer over any of the `Read` implementations
+    ///
+    /// The `from_str`, `from_slice` and `from_reader` constructors are usually
+    /// more convenient.
+    pub fn new(read: R) -> Self {
         Deserializer {
-            parser: Parser::new(input),
-            hack_val: None,
+            token_start: read.position(),
+            read,
+            scratch: Vec::new(),
+            peeked: None,
+            bytes_tag: Cow::Borrowed(BYTES_TAG),
+            tag_readers: TagReaders::new(),
+        }
+    }
+
+    /// Read byte arrays from `#tag "..."` instead of the default `#base64 "..."`,
+    /// e.g. to match a reader tag registered on the Clojure side. `to_value`
+    /// and `from_value` always use `#base64`.
+    pub fn with_bytes_tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.bytes_tag = tag.into();
+        self
+    }
+
+    /// Read tagged literals with the functions registered in `readers`,
+    /// see `TagReaders`
+    pub fn with_tag_readers(mut self, readers: TagReaders) -> Self {
+        self.tag_readers = readers;
+        self
+    }
+
+    /// Check that the input holds nothing more than whitespace, comments and
+    /// discarded forms. Call this after deserializing a value, to reject
+    /// trailing input the way `from_str` does.
+    pub fn end(&mut self) -> Result<()> {
+        if self.peeked.is_some() || self.read_token_or_eof()?.is_some() {
+            Err(self.token_error(ErrorCode::TrailingCharacters))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Turn the deserializer into an iterator over consecutive top-level forms
+    /// of type `T`, as in a log of edn events
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T>(self) -> StreamDeserializer<'de, R, T>
+    where
+        T: Deserialize<'de>,
+    {
+        StreamDeserializer {
+            de: self,
+            offset: 0,
+            failed: false,
+            output: PhantomData,
         }
     }
 }
 
+impl<'a> Deserializer<'a, StrRead<'a>> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &'a str) -> Self {
+        Deserializer::new(StrRead::new(s))
+    }
+}
+
+impl<'a> Deserializer<'a, SliceRead<'a>> {
+    pub fn from_slice(bytes: &'a [u8]) -> Self {
+        Deserializer::new(SliceRead::new(bytes))
+    }
+}
+
+impl<'de, R> Deserializer<'de, IoRead<R>>
+where
+    R: io::Read,
+{
+    pub fn from_reader(reader: R) -> Self {
+        Deserializer::new(IoRead::new(reader))
+    }
+}
+
+fn from_trait<'de, R, T>(read: R) -> Result<T>
+where
+    R: Read<'de>,
+    T: Deserialize<'de>,
+{
+    let mut deserializer = Deserializer::new(read);
+    let value = T::deserialize(&mut deserializer).map_err(|err| deserializer.fix_position(err))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
 /// Deserialize an instance of type `T` from a string of edn text
+///
+/// The input must hold exactly one value: anything after it other than
+/// whitespace, comments and discarded forms is a "trailing characters" error.
 pub fn from_str<'a, T>(s: &'a str) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_trait(StrRead::new(s))
+}
+
+/// Deserialize an instance of type `T` from the first edn form in a string,
+/// ignoring whatever follows it
+///
+/// Use `Deserializer::into_iter` to read the following forms as well.
+pub fn from_str_lenient<'a, T>(s: &'a str) -> Result<T>
 where
     T: Deserialize<'a>,
 {
     let mut deserializer = Deserializer::from_str(s);
-    T::deserialize(&mut deserializer)
+    T::deserialize(&mut deserializer).map_err(|err| deserializer.fix_position(err))
 }
 
-impl<'de> Deserializer<'de> {
-    fn parse_unsigned<T>(&mut self) -> Result<T>
-    where
-        T: AddAssign<T> + MulAssign<T> + From<u8>,
-    {
-        unimplemented!()
+/// Deserialize an instance of type `T` from bytes of edn text
+///
+/// The bytes must be valid UTF-8, anything else is reported as a syntax error.
+/// As with `from_str`, the input must hold exactly one value.
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_trait(SliceRead::new(v))
+}
+
+/// Deserialize an instance of type `T` from an I/O stream of edn text
+///
+/// The stream is read one byte at a time, to its end, and must hold exactly one
+/// value. Wrap unbuffered sources like files or sockets in an `io::BufReader`.
+pub fn from_reader<R, T>(rdr: R) -> Result<T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    from_trait(IoRead::new(rdr))
+}
+
+/// An iterator that deserializes a sequence of top-level edn forms, created by
+/// `Deserializer::into_iter`
+///
+/// Forms can be separated by whitespace, commas and comments. Iteration stops
+/// at the end of the input, or after yielding the first error.
+pub struct StreamDeserializer<'de, R, T> {
+    de: Deserializer<'de, R>,
+    offset: usize,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<'de, R, T> StreamDeserializer<'de, R, T>
+where
+    R: Read<'de>,
+    T: Deserialize<'de>,
+{
+    /// Create a stream over any of the `Read` implementations
+    pub fn new(read: R) -> Self {
+        Deserializer::new(read).into_iter()
     }
 
-    fn parse_signed<T>(&mut self) -> Result<T>
-    where
-        T: Neg<Output = T> + AddAssign<T> + MulAssign<T> + From<i8>,
-    {
-        unimplemented!()
+    /// The number of bytes of input consumed by the forms deserialized so far
+    ///
+    /// After an error, this is the offset at which the failing form starts.
+    pub fn byte_offset(&self) -> usize {
+        self.offset
     }
+}
+
+impl<'de, R, T> Iterator for StreamDeserializer<'de, R, T>
+where
+    R: Read<'de>,
+    T: Deserialize<'de>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+
+        let de = &mut self.de;
+        let result = match de.read_token_or_eof() {
+            Ok(None) => return None,
+            Ok(Some(token)) => {
+                self.offset = de.token_start.offset;
+                de.peeked = Some(token);
+                T::deserialize(&mut *de).map_err(|err| de.fix_position(err))
+            }
+            Err(err) => Err(err),
+        };
 
-    fn parse_string(&mut self) -> Result<&'de str> {
-        unimplemented!()
+        match result {
+            Ok(_) => self.offset = de.read.position().offset,
+            Err(_) => self.failed = true,
+        }
+        Some(result)
     }
+}
+
+/// The smallest meaningful pieces of edn text
+#[derive(Debug)]
+enum Token<'de> {
+    Nil,
+    Bool(bool),
+    Integer(i128),
+    /// An integer above `i128::MAX` that still fits a u128
+    U128(u128),
+    Float(f64),
+    #[cfg(feature = "bignum")]
+    BigInt(BigInt),
+    #[cfg(feature = "bignum")]
+    BigDecimal(BigDecimal),
+    String(Cow<'de, str>),
+    Char(char),
+    Symbol(Cow<'de, str>),
+    Keyword(Cow<'de, str>),
+    /// `#tag`, followed by the tagged value
+    Tag(Cow<'de, str>),
+    /// `(`, `[`, `{` or `#{`, holding the matching closing delimiter
+    Open(Collection),
+    Close(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Collection {
+    List,
+    Vector,
+    Map,
+    Set,
+}
 
-    fn read_parsed(&mut self) -> Result<EValue> {
-        if let Some(v) = self.hack_val.take() {
-            return Ok(v);
+impl Collection {
+    fn close(self) -> u8 {
+        match self {
+            Collection::List => b')',
+            Collection::Vector => b']',
+            Collection::Map | Collection::Set => b'}',
         }
+    }
 
-        match self.parser.read() {
-            Some(Ok(v)) => Ok(v),
-            None => Err(Error::Eof),
-            Some(Err(e)) => Err(if e.message.contains("EOF") {
-                Error::Eof
-            } else {
-                Error::Bad
-            }),
+    fn eof(self) -> ErrorCode {
+        match self {
+            Collection::List => ErrorCode::EofWhileParsingList,
+            Collection::Vector => ErrorCode::EofWhileParsingVector,
+            Collection::Map => ErrorCode::EofWhileParsingMap,
+            Collection::Set => ErrorCode::EofWhileParsingSet,
         }
     }
 }
 
-struct ListAccess(Vec<Value>);
+fn is_whitespace(b: u8) -> bool {
+    b == b' ' || b == b'\t' || b == b'\n' || b == b'\r' || b == b','
+}
+
+/// Characters that can appear inside symbols, keywords and numbers
+fn is_constituent(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"*+!-_?$%&=<>/.:#'".contains(&b) || b >= 0x80
+}
+
+fn is_string_content(b: u8) -> bool {
+    b != b'"' && b != b'\\'
+}
 
-impl<I> From<I> for ListAccess
+impl<'de, R> Deserializer<'de, R>
 where
-    I: IntoIterator<Item = Value>,
+    R: Read<'de>,
 {
-    fn from(i: I) -> Self {
-        Self(i.into_iter().collect())
+    fn error_at(&self, code: ErrorCode, pos: Position) -> Error {
+        let pos = self.read.fill_position(pos);
+        Error::syntax(code, pos.line, pos.column, pos.offset)
     }
-}
 
-use std::result;
-impl<'de> SeqAccess<'de> for ListAccess {
-    type Error = Error;
+    /// An error at the current position in the input
+    fn error(&self, code: ErrorCode) -> Error {
+        self.error_at(code, self.read.position())
+    }
 
-    fn next_element_seed<T>(&mut self, seed: T) -> result::Result<Option<T::Value>, Self::Error>
-    where
-        T: DeserializeSeed<'de>,
-    {
-        if self.0.is_empty() {
-            Ok(None)
+    /// An error pointing at the start of the last token read
+    fn token_error(&self, code: ErrorCode) -> Error {
+        self.error_at(code, self.token_start)
+    }
+
+    /// Point errors raised by visitors at the last token read
+    fn fix_position(&self, err: Error) -> Error {
+        err.fix_position(|code| self.token_error(code))
+    }
+
+    /// The error for a token of the wrong type, e.g. a string where a number is expected
+    fn invalid_type(&self, token: &Token, exp: &dyn Expected) -> Error {
+        let unexpected = match *token {
+            Token::Nil => Unexpected::Other("nil"),
+            Token::Bool(b) => Unexpected::Bool(b),
+            Token::Integer(i) => unexpected_integer(i),
+            Token::U128(_) => Unexpected::Other("128-bit integer"),
+            Token::Float(f) => Unexpected::Float(f),
+            #[cfg(feature = "bignum")]
+            Token::BigInt(_) => Unexpected::Other("big integer"),
+            #[cfg(feature = "bignum")]
+            Token::BigDecimal(_) => Unexpected::Other("big decimal"),
+            Token::String(ref s) => Unexpected::Str(s),
+            Token::Char(c) => Unexpected::Char(c),
+            Token::Symbol(_) => Unexpected::Other("symbol"),
+            Token::Keyword(_) => Unexpected::Other("keyword"),
+            Token::Tag(_) => Unexpected::Other("tagged literal"),
+            Token::Open(Collection::List) => Unexpected::Other("list"),
+            Token::Open(Collection::Vector) => Unexpected::Other("vector"),
+            Token::Open(Collection::Set) => Unexpected::Other("set"),
+            Token::Open(Collection::Map) => Unexpected::Map,
+            Token::Close(_) => return self.token_error(ErrorCode::UnexpectedClosingDelimiter),
+        };
+        self.fix_position(de::Error::invalid_type(unexpected, exp))
+    }
+
+    /// Skip whitespace, commas and comments, returning the next byte
+    fn skip_whitespace(&mut self) -> Result<Option<u8>> {
+        loop {
+            match self.read.peek()? {
+                Some(b) if is_whitespace(b) => self.read.discard(),
+                Some(b';') => {
+                    while let Some(b) = self.read.next()? {
+                        if b == b'\n' {
+                            break;
+                        }
+                    }
+                }
+                other => return Ok(other),
+            }
+        }
+    }
+
+    /// Read and drop one complete form
+    fn ignore_form(&mut self) -> Result<()> {
+        match self.next_token()? {
+            Token::Open(kind) => {
+                let mut len = 0;
+                while !self.at_close(kind)? {
+                    self.ignore_form()?;
+                    len += 1;
+                }
+                if kind == Collection::Map && len % 2 == 1 {
+                    return Err(self.token_error(ErrorCode::MapKeyWithoutValue));
+                }
+                Ok(())
+            }
+            Token::Tag(_) => self.ignore_form(),
+            Token::Close(_) => Err(self.token_error(ErrorCode::UnexpectedClosingDelimiter)),
+            _ => Ok(()),
+        }
+    }
+
+    fn peek_token(&mut self) -> Result<&Token<'de>> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_token(ErrorCode::EofWhileParsingValue)?);
+        }
+        Ok(self.peeked.as_ref().unwrap())
+    }
+
+    fn next_token(&mut self) -> Result<Token<'de>> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.read_token(ErrorCode::EofWhileParsingValue),
+        }
+    }
+
+    /// Peek at the next token inside a `kind` collection
+    fn peek_element(&mut self, kind: Collection) -> Result<&Token<'de>> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_token(kind.eof())?);
+        }
+        Ok(self.peeked.as_ref().unwrap())
+    }
+
+    /// Check whether the next token closes a `kind` collection, consuming it if so
+    fn at_close(&mut self, kind: Collection) -> Result<bool> {
+        match *self.peek_element(kind)? {
+            Token::Close(c) if c == kind.close() => {
+                self.peeked = None;
+                Ok(true)
+            }
+            Token::Close(_) => Err(self.token_error(ErrorCode::UnexpectedClosingDelimiter)),
+            _ => Ok(false),
+        }
+    }
+
+    /// Expect the end of a collection whose contents the visitor has finished reading
+    fn end_collection(&mut self, kind: Collection) -> Result<()> {
+        if self.at_close(kind)? {
+            Ok(())
         } else {
-            Ok(Some(seed.deserialize(self.0.remove(0))?))
+            Err(self.token_error(ErrorCode::TrailingElements))
+        }
+    }
+
+    /// Read the next token, skipping `#_` discarded forms. Running out of input
+    /// is reported with `eof`.
+    fn read_token(&mut self, eof: ErrorCode) -> Result<Token<'de>> {
+        match self.read_token_or_eof()? {
+            Some(token) => Ok(token),
+            None => Err(self.error(eof)),
+        }
+    }
+
+    /// Read the next token, or `None` at the end of the input
+    fn read_token_or_eof(&mut self) -> Result<Option<Token<'de>>> {
+        loop {
+            let b = match self.skip_whitespace()? {
+                Some(b) => b,
+                None => return Ok(None),
+            };
+            self.token_start = self.read.position();
+            if is_constituent(b) && b != b':' && b != b'#' {
+                return self.parse_symbolic().map(Some);
+            }
+            self.read.discard();
+
+            let token = match b {
+                b'(' => Ok(Token::Open(Collection::List)),
+                b'[' => Ok(Token::Open(Collection::Vector)),
+                b'{' => Ok(Token::Open(Collection::Map)),
+                b')' | b']' | b'}' => Ok(Token::Close(b)),
+                b'"' => self.parse_string().map(Token::String),
+                b'\\' => self.parse_char().map(Token::Char),
+                b':' => {
+                    let name = self.read_constituents()?;
+                    if !is_keyword_name(&name) {
+                        return Err(self.token_error(ErrorCode::InvalidKeyword));
+                    }
+                    Ok(Token::Keyword(name))
+                }
+                b'#' => {
+                    if self.read.peek()? == Some(b'_') {
+                        self.read.discard();
+                        self.ignore_form()?;
+                        continue;
+                    }
+                    self.parse_dispatch()
+                }
+                _ => Err(self.token_error(ErrorCode::UnexpectedCharacter)),
+            };
+            return token.map(Some);
+        }
+    }
+
+    /// Check bytes taken from the input for valid UTF-8
+    fn utf8<'a>(&self, bytes: &'a [u8]) -> Result<&'a str> {
+        std::str::from_utf8(bytes).map_err(|_| self.token_error(ErrorCode::InvalidUtf8))
+    }
+
+    /// Read the text of a symbol, keyword, number or tag
+    fn read_constituents(&mut self) -> Result<Cow<'de, str>> {
+        self.scratch.clear();
+        match self.read.read_while(&mut self.scratch, is_constituent)? {
+            Reference::Borrowed(b) => {
+                let b = self.utf8(b)?;
+                Ok(Cow::Borrowed(b))
+            }
+            Reference::Copied(_) => {
+                let bytes = std::mem::take(&mut self.scratch);
+                String::from_utf8(bytes)
+                    .map(Cow::Owned)
+                    .map_err(|_| self.token_error(ErrorCode::InvalidUtf8))
+            }
+        }
+    }
+
+    /// Parse what follows a `#`: sets, symbolic values and tags
+    fn parse_dispatch(&mut self) -> Result<Token<'de>> {
+        match self.read.peek()? {
+            Some(b'{') => {
+                self.read.discard();
+                Ok(Token::Open(Collection::Set))
+            }
+            Some(b'#') => {
+                self.read.discard();
+                match &*self.read_constituents()? {
+                    "Inf" => Ok(Token::Float(f64::INFINITY)),
+                    "-Inf" => Ok(Token::Float(f64::NEG_INFINITY)),
+                    "NaN" => Ok(Token::Float(f64::NAN)),
+                    _ => Err(self.token_error(ErrorCode::InvalidSymbolicValue)),
+                }
+            }
+            Some(b) if b.is_ascii_alphabetic() => {
+                let tag = self.read_constituents()?;
+                if is_symbol(&tag) {
+                    Ok(Token::Tag(tag))
+                } else {
+                    Err(self.token_error(ErrorCode::InvalidTag))
+                }
+            }
+            None => Err(self.error(ErrorCode::EofWhileParsingValue)),
+            _ => Err(self.token_error(ErrorCode::InvalidTag)),
+        }
+    }
+
+    /// Parse numbers, `nil`, booleans and symbols
+    fn parse_symbolic(&mut self) -> Result<Token<'de>> {
+        let token = self.read_constituents()?;
+        let bytes = token.as_bytes();
+
+        let digit_at = |i: usize| bytes.get(i).is_some_and(u8::is_ascii_digit);
+        let sign_at_0 = bytes[0] == b'+' || bytes[0] == b'-';
+        if digit_at(0) || (sign_at_0 && digit_at(1)) {
+            return parse_number(&token).map_err(|code| self.token_error(code));
+        }
+
+        match &*token {
+            "nil" => Ok(Token::Nil),
+            "true" => Ok(Token::Bool(true)),
+            "false" => Ok(Token::Bool(false)),
+            s if is_symbol(s) => Ok(Token::Symbol(token)),
+            _ => Err(self.token_error(ErrorCode::InvalidSymbol)),
         }
     }
+
+    /// Parse a string after the opening quote, borrowing it from the input
+    /// unless it holds escapes or comes from an `IoRead`
+    fn parse_string(&mut self) -> Result<Cow<'de, str>> {
+        self.scratch.clear();
+
+        loop {
+            // runs are only borrowed while nothing has been copied into scratch
+            let borrowed = match self.read.read_while(&mut self.scratch, is_string_content)? {
+                Reference::Borrowed(run) => Some(run),
+                Reference::Copied(_) => None,
+            };
+
+            match self.read.next()? {
+                Some(b'"') => {
+                    return match borrowed {
+                        Some(run) => self.utf8(run).map(Cow::Borrowed),
+                        None => {
+                            let bytes = std::mem::take(&mut self.scratch);
+                            String::from_utf8(bytes)
+                                .map(Cow::Owned)
+                                .map_err(|_| self.token_error(ErrorCode::InvalidUtf8))
+                        }
+                    };
+                }
+                Some(b'\\') => {
+                    if let Some(run) = borrowed {
+                        self.scratch.extend_from_slice(run);
+                    }
+                    let c = match self.read.next()? {
+                        Some(b't') => '\t',
+                        Some(b'r') => '\r',
+                        Some(b'n') => '\n',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'\\') => '\\',
+                        Some(b'"') => '"',
+                        Some(b'u') => self.parse_unicode_escape()?,
+                        Some(_) => return Err(self.error(ErrorCode::InvalidEscape)),
+                        None => return Err(self.error(ErrorCode::EofWhileParsingString)),
+                    };
+                    let mut buf = [0; 4];
+                    self.scratch
+                        .extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                _ => return Err(self.error(ErrorCode::EofWhileParsingString)),
+            }
+        }
+    }
+
+    /// Parse the 4 hex digits following `\u`
+    fn parse_unicode_escape(&mut self) -> Result<char> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = match self.read.next()? {
+                Some(b) => (b as char).to_digit(16),
+                None => return Err(self.error(ErrorCode::EofWhileParsingString)),
+            };
+            match digit {
+                Some(d) => code = code * 16 + d,
+                None => return Err(self.error(ErrorCode::InvalidEscape)),
+            }
+        }
+        std::char::from_u32(code).ok_or_else(|| self.error(ErrorCode::InvalidUnicodeCodePoint))
+    }
+
+    fn parse_char(&mut self) -> Result<char> {
+        let first = match self.read.next()? {
+            Some(b) => b,
+            None => return Err(self.error(ErrorCode::EofWhileParsingChar)),
+        };
+
+        if first >= 0x80 {
+            return self.parse_utf8_char(first);
+        }
+        if !first.is_ascii_alphanumeric() {
+            return Ok(first as char);
+        }
+
+        self.scratch.clear();
+        self.scratch.push(first);
+        let name = match self
+            .read
+            .read_while(&mut self.scratch, |b| b.is_ascii_alphanumeric())?
+        {
+            Reference::Borrowed(b) => b,
+            Reference::Copied(b) => b,
+        };
+
+        let c = match name {
+            [c] => Ok(*c as char),
+            b"newline" => Ok('\n'),
+            b"return" => Ok('\r'),
+            b"space" => Ok(' '),
+            b"tab" => Ok('\t'),
+            b"formfeed" => Ok('\u{c}'),
+            b"backspace" => Ok('\u{8}'),
+            [b'u', hex @ ..] if hex.len() == 4 => {
+                let code = std::str::from_utf8(hex)
+                    .ok()
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+                match code {
+                    Some(code) => {
+                        std::char::from_u32(code).ok_or(ErrorCode::InvalidUnicodeCodePoint)
+                    }
+                    None => Err(ErrorCode::InvalidCharName),
+                }
+            }
+            _ => Err(ErrorCode::InvalidCharName),
+        };
+        c.map_err(|code| self.token_error(code))
+    }
+
+    /// Decode a multi-byte UTF-8 character starting with `first`
+    fn parse_utf8_char(&mut self, first: u8) -> Result<char> {
+        let len = match first {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return Err(self.token_error(ErrorCode::InvalidUtf8)),
+        };
+
+        let mut buf = [first, 0, 0, 0];
+        for b in buf.iter_mut().take(len).skip(1) {
+            *b = match self.read.next()? {
+                Some(b) => b,
+                None => return Err(self.error(ErrorCode::EofWhileParsingChar)),
+            };
+        }
+        match std::str::from_utf8(&buf[..len]) {
+            Ok(s) => Ok(s.chars().next().unwrap()),
+            Err(_) => Err(self.token_error(ErrorCode::InvalidUtf8)),
+        }
+    }
+
+    /// Read the string following the tag of a byte array
+    fn parse_base64(&mut self) -> Result<Vec<u8>> {
+        match self.next_token()? {
+            Token::String(s) => decode_base64(&s).map_err(|err| self.fix_position(err)),
+            token => Err(self.invalid_type(&token, &"a base64 string")),
+        }
+    }
+
+    /// Read a tagged literal coming next into a `Value` if the tag readers
+    /// handle it differently than `deserialize_any` and the like would
+    fn read_tagged(&mut self) -> Result<Option<Value>> {
+        self.peek_token()?;
+        match self.peeked {
+            Some(Token::Tag(ref tag)) if self.tag_readers.claims(tag, &self.bytes_tag) => {
+                self.parse_value().map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Read the next form straight into a `Value`, keeping every edn construct
+    pub(crate) fn parse_value(&mut self) -> Result<Value> {
+        let token = self.next_token()?;
+        self.value_from_token(token)
+    }
+
+    fn value_from_token(&mut self, token: Token<'de>) -> Result<Value> {
+        Ok(match token {
+            Token::Nil => Value::Nil,
+            Token::Bool(b) => Value::Bool(b),
+            Token::Integer(i) => Value::Integer(i),
+            Token::U128(u) => Value::U128(u),
+            Token::Float(f) => Value::Float(f.into()),
+            #[cfg(feature = "bignum")]
+            Token::BigInt(n) => Value::BigInt(n),
+            #[cfg(feature = "bignum")]
+            Token::BigDecimal(d) => Value::BigDecimal(d),
+            Token::String(s) => Value::String(s.into_owned()),
+            Token::Char(c) => Value::Char(c),
+            Token::Symbol(s) => Value::symbol(s),
+            Token::Keyword(k) => Value::Keyword(k.into_owned()),
+            Token::Tag(tag) => {
+                let start = self.token_start;
+                let tag = Symbol {
+                    inner: tag.into_owned(),
+                };
+                let value = self.parse_value()?;
+                self.tag_readers
+                    .read(tag, value, &self.bytes_tag)
+                    .map_err(|code| self.error_at(code, start))?
+            }
+            Token::Open(Collection::List) => Value::List(self.parse_elements(Collection::List)?),
+            Token::Open(Collection::Vector) => {
+                Value::Vector(self.parse_elements(Collection::Vector)?)
+            }
+            Token::Open(Collection::Set) => {
+                let mut set = BTreeSet::new();
+                while !self.at_close(Collection::Set)? {
+                    let start = self.token_start;
+                    if !set.insert(self.parse_value()?) {
+                        return Err(self.error_at(ErrorCode::DuplicateSetElement, start));
+                    }
+                }
+                Value::Set(set)
+            }
+            Token::Open(Collection::Map) => {
+                let mut map = BTreeMap::new();
+                while !self.at_close(Collection::Map)? {
+                    let start = self.token_start;
+                    let key = self.parse_value()?;
+                    if let Token::Close(b'}') = self.peek_element(Collection::Map)? {
+                        return Err(self.token_error(ErrorCode::MapKeyWithoutValue));
+                    }
+                    let value = self.parse_value()?;
+                    if map.insert(key, value).is_some() {
+                        return Err(self.error_at(ErrorCode::DuplicateMapKey, start));
+                    }
+                }
+                Value::Map(map)
+            }
+            Token::Close(_) => return Err(self.token_error(ErrorCode::UnexpectedClosingDelimiter)),
+        })
+    }
+
+    fn parse_elements(&mut self, kind: Collection) -> Result<Vec<Value>> {
+        let mut elements = Vec::new();
+        while !self.at_close(kind)? {
+            elements.push(self.parse_value()?);
+        }
+        Ok(elements)
+    }
 }
 
-struct MapStore {
-    keys: Vec<Value>,
-    vals: Vec<Value>,
+/// Whether `name` reads back as the keyword `:name`
+pub(crate) fn is_keyword_name(name: &str) -> bool {
+    !name.starts_with(':') && name.bytes().all(is_constituent) && is_symbol(name)
 }
 
-impl<'de> MapAccess<'de> for MapStore {
-    type Error = Error;
+/// Check the rules for symbols not already enforced by tokenization
+fn is_symbol(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    let valid_start = |b: u8| !b.is_ascii_digit() && b != b':' && b != b'#' && b != b'\'';
 
-    fn next_key_seed<T>(&mut self, seed: T) -> result::Result<Option<T::Value>, Self::Error>
-    where
-        T: DeserializeSeed<'de>,
-    {
-        if self.keys.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(seed.deserialize(self.keys.remove(0))?))
+    match bytes {
+        [] => false,
+        [b'/'] => true,
+        // a leading -, + or . cannot be followed by a digit
+        [b'-', second, ..] | [b'+', second, ..] | [b'.', second, ..] if second.is_ascii_digit() => {
+            false
+        }
+        _ => {
+            // a namespace and a name, both non-empty
+            let mut parts = s.splitn(2, '/');
+            let ns_or_name = parts.next().unwrap();
+            let name = parts.next();
+
+            valid_start(bytes[0])
+                && !ns_or_name.is_empty()
+                && name.is_none_or(|n| !n.is_empty() && !n.contains('/'))
         }
     }
+}
 
-    fn next_value_seed<T>(&mut self, seed: T) -> result::Result<T::Value, Self::Error>
-    where
-        T: DeserializeSeed<'de>,
-    {
-        if self.vals.is_empty() {
-            Err(Error::Bad)
-        } else {
-            Ok(seed.deserialize(self.vals.remove(0))?)
+fn parse_number<'de>(token: &str) -> std::result::Result<Token<'de>, ErrorCode> {
+    let (body, suffix) = match token.as_bytes()[token.len() - 1] {
+        b'N' | b'M' => token.split_at(token.len() - 1),
+        _ => (token, ""),
+    };
+
+    let unsigned = body.trim_start_matches(['+', '-']);
+    let int_len = unsigned.bytes().take_while(u8::is_ascii_digit).count();
+    let (int_part, rest) = unsigned.split_at(int_len);
+
+    // edn integers cannot have leading zeros
+    if int_part.is_empty() || (int_part.len() > 1 && int_part.starts_with('0')) {
+        return Err(ErrorCode::InvalidNumber);
+    }
+
+    let body = body.trim_start_matches('+');
+
+    // without the `bignum` feature, `N` and `M` numbers are read as i128 and f64
+    #[cfg(feature = "bignum")]
+    match suffix {
+        "N" if rest.is_empty() => {
+            return body
+                .parse::<BigInt>()
+                .map(Token::BigInt)
+                .map_err(|_| ErrorCode::InvalidNumber)
+        }
+        "M" if is_float_tail(rest) => {
+            return body
+                .parse::<BigDecimal>()
+                .map(Token::BigDecimal)
+                .map_err(|_| ErrorCode::InvalidNumber)
+        }
+        _ => {}
+    }
+
+    if rest.is_empty() && suffix != "M" {
+        if let Ok(i) = body.parse::<i128>() {
+            return Ok(Token::Integer(i));
+        }
+        if let Ok(u) = body.parse::<u128>() {
+            return Ok(Token::U128(u));
         }
+        // larger ones are big integers, as in Clojure
+        #[cfg(feature = "bignum")]
+        return body
+            .parse::<BigInt>()
+            .map(Token::BigInt)
+            .map_err(|_| ErrorCode::InvalidNumber);
+        #[cfg(not(feature = "bignum"))]
+        return Err(ErrorCode::NumberOutOfRange);
+    }
+
+    if suffix == "N" || !is_float_tail(rest) {
+        return Err(ErrorCode::InvalidNumber);
+    }
+
+    body.parse::<f64>()
+        .map(Token::Float)
+        .map_err(|_| ErrorCode::InvalidNumber)
+}
+
+/// Describe an integer for error messages, as precisely as serde allows
+pub(crate) fn unexpected_integer(i: i128) -> Unexpected<'static> {
+    if let Ok(i) = i64::try_from(i) {
+        Unexpected::Signed(i)
+    } else if let Ok(u) = u64::try_from(i) {
+        Unexpected::Unsigned(u)
+    } else {
+        Unexpected::Other("128-bit integer")
+    }
+}
+
+/// Decode the base64 string of a byte array
+pub(crate) fn decode_base64<E>(s: &str) -> std::result::Result<Vec<u8>, E>
+where
+    E: de::Error,
+{
+    BASE64
+        .decode(s)
+        .map_err(|_| de::Error::invalid_value(Unexpected::Str(s), &"a base64 string"))
+}
+
+/// Hand the visitor a decoded byte array as a sequence, e.g. for a `Vec<u8>`
+pub(crate) fn visit_byte_seq<'de, V>(bytes: Vec<u8>, visitor: V) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    let mut seq = de::value::SeqDeserializer::<_, Error>::new(bytes.into_iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+/// Hand an integer to the visitor as an i64 or u64 when it fits, since many
+/// visitors do not implement the 128-bit methods
+pub(crate) fn visit_integer<'de, V, E>(visitor: V, i: i128) -> std::result::Result<V::Value, E>
+where
+    V: Visitor<'de>,
+    E: de::Error,
+{
+    if let Ok(i) = i64::try_from(i) {
+        visitor.visit_i64(i)
+    } else if let Ok(u) = u64::try_from(i) {
+        visitor.visit_u64(u)
+    } else {
+        visitor.visit_i128(i)
     }
 }
 
+/// Check the `.123e-4` part of a float
+fn is_float_tail(rest: &str) -> bool {
+    let (frac, exp) = match rest.find(['e', 'E']) {
+        Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+        None => (rest, None),
+    };
+
+    let frac_ok =
+        frac.is_empty() || (frac.starts_with('.') && frac[1..].bytes().all(|b| b.is_ascii_digit()));
+    let exp_ok = exp.is_none_or(|e| {
+        let digits = e.trim_start_matches(['+', '-']);
+        e.len() - digits.len() <= 1
+            && !digits.is_empty()
+            && digits.bytes().all(|b| b.is_ascii_digit())
+    });
+
+    frac_ok && exp_ok
+}
+
+/// Deserialize what the tag readers return in place of a tagged literal
+/// coming next, if they handle it
+macro_rules! forward_read_tagged {
+    ($de:ident, $method:ident($($arg:expr),*)) => {
+        if let Some(value) = $de.read_tagged()? {
+            return de::Deserializer::$method(value, $($arg),*)
+                .map_err(|err| $de.fix_position(err));
+        }
+    };
+}
+
 macro_rules! deserialize_integer {
     ($method:ident, $int:ty, $visit_method:ident) => {
         fn $method<V>(self, visitor: V) -> Result<V::Value>
         where
             V: Visitor<'de>,
         {
-            let parsed = self.read_parsed()?;
-
-            match parsed {
-                EValue::Integer(i) => {
-                    let conv = <$int as num_traits::NumCast>::from(i).ok_or(Error::NumericOutOfBounds)?;
-                    visitor.$visit_method(conv)
-                }
-                _ => Err(Error::Bad),
+            forward_read_tagged!(self, $method(visitor));
+            match self.next_token()? {
+                Token::Integer(i) => match <$int as num_traits::NumCast>::from(i) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(self
+                        .fix_position(de::Error::invalid_value(unexpected_integer(i), &visitor))),
+                },
+                Token::U128(u) => match <$int as num_traits::NumCast>::from(u) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(self.fix_position(de::Error::invalid_value(
+                        Unexpected::Other("128-bit integer"),
+                        &visitor,
+                    ))),
+                },
+                #[cfg(feature = "bignum")]
+                Token::BigInt(n) => match <$int as num_traits::NumCast>::from(n) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(self.fix_position(de::Error::invalid_value(
+                        Unexpected::Other("big integer"),
+                        &visitor,
+                    ))),
+                },
+                token => Err(self.invalid_type(&token, &visitor)),
             }
         }
-    }
+    };
 }
 
 macro_rules! deserialize_float {
@@ -159,39 +995,78 @@ macro_rules! deserialize_float {
         where
             V: Visitor<'de>,
         {
-            let parsed = self.read_parsed()?;
-
-            match parsed {
-                EValue::Float(i) => {
-                    let conv = <$float as num_traits::NumCast>::from(i.into_inner()).ok_or(Error::NumericOutOfBounds)?;
-                    visitor.$visit_method(conv)
-                }
-                _ => Err(Error::Bad),
+            forward_read_tagged!(self, $method(visitor));
+            match self.next_token()? {
+                Token::Float(f) => match <$float as num_traits::NumCast>::from(f) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => {
+                        Err(self
+                            .fix_position(de::Error::invalid_value(Unexpected::Float(f), &visitor)))
+                    }
+                },
+                #[cfg(feature = "bignum")]
+                Token::BigDecimal(d) => match <$float as num_traits::NumCast>::from(d) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(self.fix_position(de::Error::invalid_value(
+                        Unexpected::Other("big decimal"),
+                        &visitor,
+                    ))),
+                },
+                token => Err(self.invalid_type(&token, &visitor)),
             }
         }
-    }
+    };
 }
 
-impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de, R> de::Deserializer<'de> for &mut Deserializer<'de, R>
+where
+    R: Read<'de>,
+{
     type Error = Error;
 
+    // `Value` keeps every construct by asking for `VALUE_TOKEN` instead. Contents
+    // serde buffers through here, e.g. for `#[serde(flatten)]`, lose the
+    // difference between keywords and strings, sets and vectors, and big numbers
+    // stay the private maps they are handed out as.
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let parsed = self.read_parsed()?;
-
-        match parsed {
-            EValue::Nil => visitor.visit_unit(),
-            EValue::Boolean(b) => visitor.visit_bool(b),
-            EValue::String(s) => visitor.visit_str(&s),
-            EValue::Char(c) => visitor.visit_char(c),
-            EValue::Integer(i) => visitor.visit_i64(i),
-            EValue::Float(f) => visitor.visit_f64(f.into_inner()),
-            EValue::List(l) => {
-                visitor.visit_seq(ListAccess(l.into_iter().map(Value::from).collect()))
+        forward_read_tagged!(self, deserialize_any(visitor));
+        match self.next_token()? {
+            Token::Nil => visitor.visit_unit(),
+            Token::Bool(b) => visitor.visit_bool(b),
+            Token::String(Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            Token::String(Cow::Owned(s)) => visitor.visit_string(s),
+            Token::Char(c) => visitor.visit_char(c),
+            // keywords and symbols are names, just like struct fields and variants
+            Token::Keyword(Cow::Borrowed(s)) | Token::Symbol(Cow::Borrowed(s)) => {
+                visitor.visit_borrowed_str(s)
+            }
+            Token::Keyword(Cow::Owned(s)) | Token::Symbol(Cow::Owned(s)) => visitor.visit_string(s),
+            Token::Integer(i) => visit_integer(visitor, i),
+            Token::U128(u) => visitor.visit_u128(u),
+            Token::Float(f) => visitor.visit_f64(f),
+            Token::Tag(tag) => visitor.visit_seq(TaggedPair::new(self, tag)),
+            #[cfg(feature = "bignum")]
+            Token::BigInt(n) => {
+                visit_private_map(visitor, bignum_entry(BIGINT_TOKEN, n.to_string()))
+            }
+            #[cfg(feature = "bignum")]
+            Token::BigDecimal(d) => {
+                visit_private_map(visitor, bignum_entry(BIGDEC_TOKEN, d.to_string()))
+            }
+            Token::Open(kind @ Collection::Map) => {
+                let value = visitor.visit_map(Entries::new(self))?;
+                self.end_collection(kind)?;
+                Ok(value)
+            }
+            Token::Open(kind) => {
+                let value = visitor.visit_seq(Elements::new(self, kind))?;
+                self.end_collection(kind)?;
+                Ok(value)
             }
-            other => panic!("unhandled case {:?}", other),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
@@ -199,11 +1074,10 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let parsed = self.read_parsed()?;
-
-        match parsed {
-            EValue::Boolean(b) => visitor.visit_bool(b),
-            _ => Err(Error::Bad),
+        forward_read_tagged!(self, deserialize_bool(visitor));
+        match self.next_token()? {
+            Token::Bool(b) => visitor.visit_bool(b),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
@@ -211,11 +1085,13 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     deserialize_integer!(deserialize_i16, i16, visit_i16);
     deserialize_integer!(deserialize_i32, i32, visit_i32);
     deserialize_integer!(deserialize_i64, i64, visit_i64);
+    deserialize_integer!(deserialize_i128, i128, visit_i128);
 
     deserialize_integer!(deserialize_u8, u8, visit_u8);
     deserialize_integer!(deserialize_u16, u16, visit_u16);
     deserialize_integer!(deserialize_u32, u32, visit_u32);
     deserialize_integer!(deserialize_u64, u64, visit_u64);
+    deserialize_integer!(deserialize_u128, u128, visit_u128);
 
     deserialize_float!(deserialize_f32, f32, visit_f32);
     deserialize_float!(deserialize_f64, f64, visit_f64);
@@ -224,11 +1100,10 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let parsed = self.read_parsed()?;
-
-        match parsed {
-            EValue::Char(c) => visitor.visit_char(c),
-            _ => Err(Error::Bad),
+        forward_read_tagged!(self, deserialize_char(visitor));
+        match self.next_token()? {
+            Token::Char(c) => visitor.visit_char(c),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
@@ -236,46 +1111,58 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::String(s) => visitor.visit_string(s),
-            _ => Err(Error::Bad),
-        }
+        self.deserialize_string(visitor)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::String(s) => visitor.visit_string(s),
-            _ => Err(Error::Bad),
+        forward_read_tagged!(self, deserialize_string(visitor));
+        match self.next_token()? {
+            Token::String(Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            Token::String(Cow::Owned(s)) => visitor.visit_string(s),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        self.deserialize_byte_buf(visitor)
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value>
+    /// Byte arrays are read from `#base64 "..."` or from a vector or list of integers
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        forward_read_tagged!(self, deserialize_byte_buf(visitor));
+        match self.next_token()? {
+            Token::Tag(ref tag) if *tag == self.bytes_tag => {
+                let bytes = self.parse_base64()?;
+                visitor.visit_byte_buf(bytes)
+            }
+            token @ Token::Open(Collection::Vector) | token @ Token::Open(Collection::List) => {
+                self.peeked = Some(token);
+                let bytes = Vec::<u8>::deserialize(&mut *self)?;
+                visitor.visit_byte_buf(bytes)
+            }
+            token => Err(self.invalid_type(&token, &visitor)),
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::Nil => visitor.visit_none(),
-            other => {
-                self.hack_val = Some(other);
-                visitor.visit_some(self)
+        match self.peek_token()? {
+            Token::Nil => {
+                self.peeked = None;
+                visitor.visit_none()
             }
+            _ => visitor.visit_some(self),
         }
     }
 
@@ -283,56 +1170,79 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::Nil => visitor.visit_unit(),
-            _ => Err(Error::Bad),
+        forward_read_tagged!(self, deserialize_unit(visitor));
+        match self.next_token()? {
+            Token::Nil => visitor.visit_unit(),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
+    /// Unit structs are written as `nil`, and read from it as well as from an
+    /// empty list or vector, or from their name as a symbol
     fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::Vector(ref v) if v.is_empty() => visitor.visit_unit(),
-            EValue::List(ref v) if v.is_empty() => visitor.visit_unit(),
-            EValue::Symbol(ref s) if s.as_str() == name => visitor.visit_unit(),
-            _ => Err(Error::Bad),
+        match self.next_token()? {
+            Token::Nil => visitor.visit_unit(),
+            Token::Open(kind @ Collection::List) | Token::Open(kind @ Collection::Vector) => {
+                self.end_collection(kind)?;
+                visitor.visit_unit()
+            }
+            Token::Symbol(s) if s == name => visitor.visit_unit(),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    /// Newtype structs are read from the wrapped value itself, the way they
+    /// are written
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::List(mut l) | EValue::Vector(mut l) => {
-                let val = l.pop();
-                if val.is_some() && l.is_empty() {
-                    self.hack_val = val;
-                    visitor.visit_newtype_struct(self)
-                } else {
-                    Err(Error::Bad)
-                }
-            }
-            v => {
-                self.hack_val = Some(v);
-                visitor.visit_newtype_struct(self)
+        // requested by `Value`, which gets exactly what `Value::from_str` would parse
+        if name == VALUE_TOKEN {
+            let value = self.parse_value()?;
+            return de::Deserializer::deserialize_newtype_struct(value, name, visitor)
+                .map_err(|err| self.fix_position(err));
+        }
+
+        // requested by `serde_edn::bignum`, which parses the digits itself
+        #[cfg(feature = "bignum")]
+        if name == BIGINT_TOKEN || name == BIGDEC_TOKEN {
+            return match self.next_token()? {
+                Token::BigInt(n) => visitor.visit_string(n.to_string()),
+                Token::BigDecimal(d) => visitor.visit_string(d.to_string()),
+                Token::Integer(i) => visit_integer(visitor, i),
+                Token::U128(u) => visitor.visit_u128(u),
+                Token::Float(f) => visitor.visit_f64(f),
+                token => Err(self.invalid_type(&token, &visitor)),
             }
+            .map_err(|err| self.fix_position(err));
         }
+
+        forward_read_tagged!(self, deserialize_newtype_struct(name, visitor));
+        visitor.visit_newtype_struct(self)
     }
 
-    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::Set(l) => visitor.visit_seq(ListAccess::from(l.into_iter().map(Value::from))),
-            EValue::Vector(l) => {
-                visitor.visit_seq(ListAccess::from(l.into_iter().map(Value::from)))
+        forward_read_tagged!(self, deserialize_seq(visitor));
+        match self.next_token()? {
+            token @ Token::Open(Collection::Map) => Err(self.invalid_type(&token, &visitor)),
+            // e.g. a `Vec<u8>` written by Clojure as base64
+            Token::Tag(ref tag) if *tag == self.bytes_tag => {
+                let bytes = self.parse_base64()?;
+                visit_byte_seq(bytes, visitor)
+            }
+            Token::Open(kind) => {
+                let value = visitor.visit_seq(Elements::new(self, kind))?;
+                self.end_collection(kind)?;
+                Ok(value)
             }
-            EValue::List(l) => visitor.visit_seq(ListAccess::from(l.into_iter().map(Value::from))),
-            _ => Err(Error::Bad),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
@@ -340,15 +1250,14 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::List(l) | EValue::Vector(l) => {
-                if l.len() == len {
-                    visitor.visit_seq(ListAccess::from(l.into_iter().map(Value::from)))
-                } else {
-                    Err(Error::Bad)
-                }
+        forward_read_tagged!(self, deserialize_tuple(len, visitor));
+        match self.next_token()? {
+            Token::Open(kind @ Collection::List) | Token::Open(kind @ Collection::Vector) => {
+                let value = visitor.visit_seq(Elements::new(self, kind))?;
+                self.end_collection(kind)?;
+                Ok(value)
             }
-            _ => Err(Error::Bad),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
@@ -361,31 +1270,21 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::List(l) | EValue::Vector(l) => {
-                if l.len() == len {
-                    visitor.visit_seq(ListAccess::from(l.into_iter().map(Value::from)))
-                } else {
-                    Err(Error::Bad)
-                }
-            }
-            _ => Err(Error::Bad),
-        }
+        self.deserialize_tuple(len, visitor)
     }
 
-    fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.read_parsed()? {
-            EValue::Map(m) => {
-                let (keys, vals): (Vec<_>, Vec<_>) = m
-                    .into_iter()
-                    .map(|(k, v)| (Value::from(k), Value::from(v)))
-                    .unzip();
-                visitor.visit_map(MapStore { keys, vals })
+        forward_read_tagged!(self, deserialize_map(visitor));
+        match self.next_token()? {
+            Token::Open(kind @ Collection::Map) => {
+                let value = visitor.visit_map(Entries::new(self))?;
+                self.end_collection(kind)?;
+                Ok(value)
             }
-            _ => Err(Error::Bad),
+            token => Err(self.invalid_type(&token, &visitor)),
         }
     }
 
@@ -401,29 +1300,305 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.deserialize_map(visitor)
     }
 
+    /// Unit variants are read from keywords or symbols, the others from
+    /// `{:variant value}`, `#ns/variant value` or `(:variant values...)`
     fn deserialize_enum<V>(
         self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
+        name: &'static str,
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        forward_read_tagged!(self, deserialize_enum(name, variants, visitor));
+        match self.next_token()? {
+            Token::Keyword(name) | Token::Symbol(name) => {
+                visitor.visit_enum(name.into_deserializer())
+            }
+            Token::Tag(tag) => {
+                // the namespace of the tag is left out of the variant name
+                let name = match tag {
+                    Cow::Borrowed(tag) => Cow::Borrowed(tag.rsplit('/').next().unwrap()),
+                    Cow::Owned(tag) => Cow::Owned(tag.rsplit('/').next().unwrap().to_owned()),
+                };
+                visitor.visit_enum(Variant::new(self, VariantForm::Tagged, Some(name)))
+            }
+            Token::Open(kind @ Collection::Map) => {
+                let value = visitor.visit_enum(Variant::new(self, VariantForm::Map, None))?;
+                self.end_collection(kind)?;
+                Ok(value)
+            }
+            Token::Open(kind @ Collection::List) => {
+                let value = visitor.visit_enum(Variant::new(self, VariantForm::List, None))?;
+                self.end_collection(kind)?;
+                Ok(value)
+            }
+            token => Err(self.invalid_type(&token, &visitor)),
+        }
     }
 
+    /// Struct fields and variant names, given as keywords, symbols or strings
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        forward_read_tagged!(self, deserialize_identifier(visitor));
+        match self.next_token()? {
+            Token::Keyword(name) | Token::Symbol(name) | Token::String(name) => match name {
+                Cow::Borrowed(name) => visitor.visit_borrowed_str(name),
+                Cow::Owned(name) => visitor.visit_string(name),
+            },
+            token => Err(self.invalid_type(&token, &visitor)),
+        }
     }
 
+    /// Skip a form, e.g. the value of an unknown struct field, checking only its syntax
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        self.ignore_form()?;
+        visitor.visit_unit()
+    }
+}
+
+/// Gives access to the elements of a list, vector or set, up to (but not
+/// including) the closing delimiter
+struct Elements<'a, 'de, R> {
+    de: &'a mut Deserializer<'de, R>,
+    kind: Collection,
+}
+
+impl<'a, 'de, R> Elements<'a, 'de, R> {
+    fn new(de: &'a mut Deserializer<'de, R>, kind: Collection) -> Self {
+        Elements { de, kind }
+    }
+}
+
+impl<'de, 'a, R> SeqAccess<'de> for Elements<'a, 'de, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match *self.de.peek_element(self.kind)? {
+            Token::Close(c) if c == self.kind.close() => Ok(None),
+            Token::Close(_) => Err(self.de.token_error(ErrorCode::UnexpectedClosingDelimiter)),
+            _ => match seed.deserialize(&mut *self.de) {
+                Ok(value) => Ok(Some(value)),
+                Err(err) => Err(self.de.fix_position(err)),
+            },
+        }
+    }
+}
+
+/// Hands `deserialize_any` a tagged literal as the pair `(tag value)`, the way
+/// `Tagged` serializes for other formats
+struct TaggedPair<'a, 'de, R> {
+    de: &'a mut Deserializer<'de, R>,
+    tag: Option<Cow<'de, str>>,
+    done: bool,
+}
+
+impl<'a, 'de, R> TaggedPair<'a, 'de, R> {
+    fn new(de: &'a mut Deserializer<'de, R>, tag: Cow<'de, str>) -> Self {
+        TaggedPair {
+            de,
+            tag: Some(tag),
+            done: false,
+        }
+    }
+}
+
+impl<'de, 'a, R> SeqAccess<'de> for TaggedPair<'a, 'de, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(tag) = self.tag.take() {
+            let tag: de::value::CowStrDeserializer<Error> = tag.into_deserializer();
+            return seed.deserialize(tag).map(Some);
+        }
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        match seed.deserialize(&mut *self.de) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) => Err(self.de.fix_position(err)),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.tag.is_some() as usize + !self.done as usize)
+    }
+}
+
+/// Gives access to the entries of a map, up to (but not including) the closing `}`
+struct Entries<'a, 'de, R> {
+    de: &'a mut Deserializer<'de, R>,
+}
+
+impl<'a, 'de, R> Entries<'a, 'de, R> {
+    fn new(de: &'a mut Deserializer<'de, R>) -> Self {
+        Entries { de }
+    }
+}
+
+impl<'de, 'a, R> MapAccess<'de> for Entries<'a, 'de, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match *self.de.peek_element(Collection::Map)? {
+            Token::Close(b'}') => Ok(None),
+            Token::Close(_) => Err(self.de.token_error(ErrorCode::UnexpectedClosingDelimiter)),
+            _ => match seed.deserialize(&mut *self.de) {
+                Ok(value) => Ok(Some(value)),
+                Err(err) => Err(self.de.fix_position(err)),
+            },
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match *self.de.peek_element(Collection::Map)? {
+            Token::Close(b'}') => Err(self.de.token_error(ErrorCode::MapKeyWithoutValue)),
+            Token::Close(_) => Err(self.de.token_error(ErrorCode::UnexpectedClosingDelimiter)),
+            _ => seed
+                .deserialize(&mut *self.de)
+                .map_err(|err| self.de.fix_position(err)),
+        }
+    }
+}
+
+/// Where the name and the contents of an enum variant are found
+#[derive(Clone, Copy, PartialEq)]
+enum VariantForm {
+    /// `{:variant value}`, the name being the only key
+    Map,
+    /// `#ns/variant value`, the name coming from the tag
+    Tagged,
+    /// `(:variant values...)`, the contents being the rest of the list
+    List,
+}
+
+/// Gives access to the name and contents of an enum variant, up to (but not
+/// including) the closing delimiter of its map or list
+struct Variant<'a, 'de, R> {
+    de: &'a mut Deserializer<'de, R>,
+    form: VariantForm,
+    // the variant name, when taken from a tag
+    name: Option<Cow<'de, str>>,
+}
+
+impl<'a, 'de, R> Variant<'a, 'de, R> {
+    fn new(
+        de: &'a mut Deserializer<'de, R>,
+        form: VariantForm,
+        name: Option<Cow<'de, str>>,
+    ) -> Self {
+        Variant { de, form, name }
+    }
+}
+
+impl<'de, 'a, R> EnumAccess<'de> for Variant<'a, 'de, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        if let Some(name) = self.name.take() {
+            let variant = seed
+                .deserialize(name.into_deserializer())
+                .map_err(|err| self.de.fix_position(err))?;
+            return Ok((variant, self));
+        }
+
+        let (kind, expected) = match self.form {
+            VariantForm::List => (Collection::List, "a list headed by the variant name"),
+            _ => (Collection::Map, "a map with a single key"),
+        };
+        if let Token::Close(_) = *self.de.peek_element(kind)? {
+            let err = de::Error::invalid_length(0, &expected);
+            return Err(self.de.fix_position(err));
+        }
+
+        let variant = seed
+            .deserialize(&mut *self.de)
+            .map_err(|err| self.de.fix_position(err))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a, R> de::VariantAccess<'de> for Variant<'a, 'de, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.form {
+            // `(:variant)`, while the other forms need some value, e.g. `{:variant nil}`
+            VariantForm::List => Ok(()),
+            _ => <()>::deserialize(&mut *self.de).map_err(|err| self.de.fix_position(err)),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+            .map_err(|err| self.de.fix_position(err))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.form {
+            VariantForm::List => visitor.visit_seq(Elements::new(self.de, Collection::List)),
+            _ => de::Deserializer::deserialize_seq(&mut *self.de, visitor),
+        }
     }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(&mut *self.de, visitor)
+    }
+}
+
+/// The single-entry map through which `deserialize_any` hands out big numbers,
+/// since serde has no type for them. The `Value` visitor turns it back into a number.
+#[cfg(feature = "bignum")]
+fn bignum_entry(
+    token: &'static str,
+    digits: String,
+) -> de::value::MapDeserializer<'static, std::iter::Once<(&'static str, String)>, Error> {
+    de::value::MapDeserializer::new(std::iter::once((token, digits)))
 }
@@ -1,26 +1,276 @@
-#![allow(warnings)]
-/// An error that can occur during `edn` deserialization
-#[derive(Debug, PartialEq, Clone)]
-pub enum Error {
-    Bad,
-    NumericOutOfBounds,
+use serde::{de, ser};
+use std::fmt::{self, Debug, Display};
+use std::io;
+
+/// An error that can occur during `edn` serialization or deserialization
+///
+/// Errors coming from the parser carry the position in the input at which
+/// they were detected.
+pub struct Error {
+    // boxed to keep `Result<T, Error>` the size of a pointer
+    err: Box<ErrorImpl>,
+}
+
+struct ErrorImpl {
+    code: ErrorCode,
+    line: usize,
+    column: usize,
+    offset: usize,
+}
+
+pub(crate) enum ErrorCode {
+    /// A message from a `Visitor`, or from `Serialize`/`Deserialize` impls
+    Message(Box<str>),
+    Io(io::Error),
+
+    EofWhileParsingValue,
+    EofWhileParsingList,
+    EofWhileParsingVector,
+    EofWhileParsingMap,
+    EofWhileParsingSet,
+    EofWhileParsingString,
+    EofWhileParsingChar,
+
+    InvalidUtf8,
+    InvalidNumber,
+    /// An integer beyond u128, only without the `bignum` feature
+    #[cfg_attr(feature = "bignum", allow(dead_code))]
+    NumberOutOfRange,
+    InvalidEscape,
+    InvalidUnicodeCodePoint,
+    InvalidCharName,
+    InvalidSymbol,
+    InvalidKeyword,
+    InvalidTag,
+    /// The string of an `#inst` is not an RFC 3339 timestamp
+    InvalidInst,
+    /// The string of a `#uuid` is not a hyphenated UUID
+    InvalidUuid,
+    /// A `##` value other than `##Inf`, `##-Inf` and `##NaN`
+    InvalidSymbolicValue,
+    UnexpectedCharacter,
+    UnexpectedClosingDelimiter,
+    MapKeyWithoutValue,
+    DuplicateMapKey,
+    DuplicateSetElement,
+    /// A collection holds more elements than the type being deserialized
+    TrailingElements,
+    /// A tagged literal without a reader, with `UnknownTag::Error`
+    UnknownTag(Box<str>),
+    /// More input after the top-level value
+    TrailingCharacters,
+}
+
+/// The broad kind of an `Error`, see `Error::classify`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Category {
+    /// Failure to read or write bytes on an I/O stream
+    Io,
+
+    /// Input that is not syntactically valid edn
+    Syntax,
+
+    /// Valid edn that does not match the type being deserialized,
+    /// or a value that cannot be serialized
+    Data,
+
+    /// The input ended in the middle of a value
     Eof,
 }
 
-impl std::error::Error for Error {}
+impl Error {
+    /// One-based line of the input at which the error was detected,
+    /// or 0 if it did not come from parsing text
+    pub fn line(&self) -> usize {
+        self.err.line
+    }
+
+    /// One-based column, counted in characters, at which the error was detected,
+    /// or 0 if it did not come from parsing text
+    pub fn column(&self) -> usize {
+        self.err.column
+    }
+
+    /// Byte offset into the input at which the error was detected
+    pub fn offset(&self) -> usize {
+        self.err.offset
+    }
+
+    /// Categorize the error by its cause
+    pub fn classify(&self) -> Category {
+        match self.err.code {
+            ErrorCode::Message(_) => Category::Data,
+            ErrorCode::Io(_) => Category::Io,
+            ErrorCode::EofWhileParsingValue
+            | ErrorCode::EofWhileParsingList
+            | ErrorCode::EofWhileParsingVector
+            | ErrorCode::EofWhileParsingMap
+            | ErrorCode::EofWhileParsingSet
+            | ErrorCode::EofWhileParsingString
+            | ErrorCode::EofWhileParsingChar => Category::Eof,
+            ErrorCode::InvalidUtf8
+            | ErrorCode::InvalidNumber
+            | ErrorCode::NumberOutOfRange
+            | ErrorCode::InvalidEscape
+            | ErrorCode::InvalidUnicodeCodePoint
+            | ErrorCode::InvalidCharName
+            | ErrorCode::InvalidSymbol
+            | ErrorCode::InvalidKeyword
+            | ErrorCode::InvalidTag
+            | ErrorCode::InvalidInst
+            | ErrorCode::InvalidUuid
+            | ErrorCode::InvalidSymbolicValue
+            | ErrorCode::UnexpectedCharacter
+            | ErrorCode::UnexpectedClosingDelimiter
+            | ErrorCode::MapKeyWithoutValue
+            | ErrorCode::DuplicateMapKey
+            | ErrorCode::DuplicateSetElement
+            | ErrorCode::TrailingCharacters => Category::Syntax,
+            ErrorCode::TrailingElements | ErrorCode::UnknownTag(_) => Category::Data,
+        }
+    }
+
+    pub fn is_io(&self) -> bool {
+        self.classify() == Category::Io
+    }
+
+    pub fn is_syntax(&self) -> bool {
+        self.classify() == Category::Syntax
+    }
+
+    pub fn is_data(&self) -> bool {
+        self.classify() == Category::Data
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.classify() == Category::Eof
+    }
+
+    pub(crate) fn syntax(code: ErrorCode, line: usize, column: usize, offset: usize) -> Self {
+        Error {
+            err: Box::new(ErrorImpl {
+                code,
+                line,
+                column,
+                offset,
+            }),
+        }
+    }
+
+    pub(crate) fn io(error: io::Error) -> Self {
+        Error::from(ErrorCode::Io(error))
+    }
+
+    /// Attach a position to an error raised without one, e.g. by a `Visitor`
+    pub(crate) fn fix_position<F>(self, f: F) -> Self
+    where
+        F: FnOnce(ErrorCode) -> Error,
+    {
+        if self.err.line == 0 {
+            f(self.err.code)
+        } else {
+            self
+        }
+    }
+}
+
+impl From<ErrorCode> for Error {
+    fn from(code: ErrorCode) -> Self {
+        Error::syntax(code, 0, 0, 0)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.err.code {
+            ErrorCode::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// io::Error has no equality of its own, so those compare by kind only
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        let (a, b) = (&*self.err, &*other.err);
+        let same_code = match (&a.code, &b.code) {
+            (ErrorCode::Io(a), ErrorCode::Io(b)) => a.kind() == b.kind(),
+            (a, b) => a.to_string() == b.to_string(),
+        };
+        same_code && a.line == b.line && a.column == b.column && a.offset == b.offset
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorCode::Message(msg) => f.write_str(msg),
+            ErrorCode::Io(e) => Display::fmt(e, f),
+            ErrorCode::EofWhileParsingValue => f.write_str("EOF while parsing a value"),
+            ErrorCode::EofWhileParsingList => f.write_str("EOF while parsing a list"),
+            ErrorCode::EofWhileParsingVector => f.write_str("EOF while parsing a vector"),
+            ErrorCode::EofWhileParsingMap => f.write_str("EOF while parsing a map"),
+            ErrorCode::EofWhileParsingSet => f.write_str("EOF while parsing a set"),
+            ErrorCode::EofWhileParsingString => f.write_str("EOF while parsing a string"),
+            ErrorCode::EofWhileParsingChar => f.write_str("EOF while parsing a character"),
+            ErrorCode::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            ErrorCode::InvalidNumber => f.write_str("invalid number"),
+            ErrorCode::NumberOutOfRange => f.write_str("number out of range"),
+            ErrorCode::InvalidEscape => f.write_str("invalid escape"),
+            ErrorCode::InvalidUnicodeCodePoint => f.write_str("invalid unicode code point"),
+            ErrorCode::InvalidCharName => f.write_str("invalid character name"),
+            ErrorCode::InvalidSymbol => f.write_str("invalid symbol"),
+            ErrorCode::InvalidKeyword => f.write_str("invalid keyword"),
+            ErrorCode::InvalidTag => f.write_str("invalid tag"),
+            ErrorCode::InvalidInst => f.write_str("invalid #inst timestamp"),
+            ErrorCode::InvalidUuid => f.write_str("invalid #uuid"),
+            ErrorCode::InvalidSymbolicValue => f.write_str("invalid symbolic value"),
+            ErrorCode::UnexpectedCharacter => f.write_str("unexpected character"),
+            ErrorCode::UnexpectedClosingDelimiter => f.write_str("unexpected closing delimiter"),
+            ErrorCode::MapKeyWithoutValue => f.write_str("map key without a value"),
+            ErrorCode::DuplicateMapKey => f.write_str("duplicate map key"),
+            ErrorCode::DuplicateSetElement => f.write_str("duplicate set element"),
+            ErrorCode::TrailingElements => f.write_str("too many elements in collection"),
+            ErrorCode::TrailingCharacters => f.write_str("trailing characters"),
+            ErrorCode::UnknownTag(tag) => write!(f, "no reader for the tag #{}", tag),
+        }
+    }
+}
 
 impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "BULLSHIIIIIT!!!!")
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.err.line == 0 {
+            Display::fmt(&self.err.code, f)
+        } else {
+            write!(
+                f,
+                "{} at line {} column {}",
+                self.err.code, self.err.line, self.err.column
+            )
+        }
     }
 }
 
-use serde::de;
-use std::fmt::Display;
+impl Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Error({:?}, line: {}, column: {})",
+            self.err.code.to_string(),
+            self.err.line,
+            self.err.column
+        )
+    }
+}
 
 impl de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
-        //Error::Message(msg.to_string())
-        Error::Bad
+        Error::from(ErrorCode::Message(msg.to_string().into_boxed_str()))
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::from(ErrorCode::Message(msg.to_string().into_boxed_str()))
     }
 }
@@ -0,0 +1,413 @@
+//! The timestamp of `#inst` tagged literals
+//!
+//! ```
+//! use serde_edn::{Inst, Tagged, Value};
+//!
+//! let value: Value = r#"#inst "1985-04-12T23:20:50.52-05:00""#.parse().unwrap();
+//! let inst = match value {
+//!     Value::Tagged(Tagged::Inst(inst)) => inst,
+//!     _ => unreachable!(),
+//! };
+//! assert_eq!(inst.unix_timestamp(), 482214050);
+//! assert_eq!(inst.offset_minutes(), -300);
+//! assert_eq!(inst.to_string(), "1985-04-12T23:20:50.52-05:00");
+//! ```
+//!
+//! With the `chrono` and `time` features, `Inst` converts to and from
+//! `chrono::DateTime` and `time::OffsetDateTime` through `TryFrom`.
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, ErrorCode};
+use crate::value::TAGGED_TOKEN;
+
+const SECS_PER_DAY: i64 = 86_400;
+// 0000-01-01T00:00:00 and 9999-12-31T23:59:59 as seconds since the epoch, the
+// range of local times four-digit years can write
+const MIN_LOCAL_SECS: i64 = -62_167_219_200;
+const MAX_LOCAL_SECS: i64 = 253_402_300_799;
+
+/// A point in time, as read from `#inst "1985-04-12T23:20:50.52Z"`
+///
+/// Keeps nanosecond precision and the UTC offset the timestamp was written
+/// with, two values for the same instant but different offsets being distinct.
+///
+/// The date in that offset is between the years 0000 and 9999, which RFC 3339
+/// can write. Leap seconds (`23:59:60`) are rejected, Unix time having no room
+/// for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Inst {
+    // seconds since 1970-01-01T00:00:00Z
+    secs: i64,
+    nanos: u32,
+    offset_minutes: i16,
+}
+
+impl Inst {
+    /// Build a timestamp from the seconds and nanoseconds since the Unix epoch,
+    /// to be written with the given UTC offset
+    ///
+    /// Returns `None` if `nanos` is not below one second, the offset is not
+    /// under a day, or the date in that offset is outside the years 0000 to 9999.
+    pub fn from_unix(secs: i64, nanos: u32, offset_minutes: i16) -> Option<Inst> {
+        if nanos >= 1_000_000_000 || offset_minutes.unsigned_abs() >= 24 * 60 {
+            return None;
+        }
+        let local = secs.checked_add(offset_minutes as i64 * 60)?;
+        if !(MIN_LOCAL_SECS..=MAX_LOCAL_SECS).contains(&local) {
+            return None;
+        }
+        Some(Inst {
+            secs,
+            nanos,
+            offset_minutes,
+        })
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z
+    pub fn unix_timestamp(&self) -> i64 {
+        self.secs
+    }
+
+    /// The fraction of the second, in nanoseconds
+    pub fn nanosecond(&self) -> u32 {
+        self.nanos
+    }
+
+    /// The UTC offset the timestamp is written with, in minutes east of UTC
+    pub fn offset_minutes(&self) -> i16 {
+        self.offset_minutes
+    }
+
+    /// Parse `yyyy[-MM[-dd[Thh[:mm[:ss[.fff]]]]]][Z|±hh:mm]`, the parts left
+    /// out being the start of the year, month, etc.
+    fn parse(s: &str) -> Option<Inst> {
+        let mut p = Parser { s: s.as_bytes() };
+
+        let year = p.digits(4)?;
+        let mut month = 1;
+        let mut day = 1;
+        let (mut hour, mut minute, mut second, mut nanos) = (0, 0, 0, 0);
+        if p.eat(b'-') {
+            month = p.digits(2)?;
+            if p.eat(b'-') {
+                day = p.digits(2)?;
+                if p.eat(b'T') {
+                    hour = p.digits(2)?;
+                    if p.eat(b':') {
+                        minute = p.digits(2)?;
+                        if p.eat(b':') {
+                            second = p.digits(2)?;
+                            if p.eat(b'.') {
+                                nanos = p.fraction()?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let offset_minutes = if p.eat(b'Z') || p.s.is_empty() {
+            0
+        } else {
+            let sign = match p.s[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            p.s = &p.s[1..];
+            let hours = p.digits(2)?;
+            if !p.eat(b':') {
+                return None;
+            }
+            let minutes = p.digits(2)?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            sign * (hours * 60 + minutes) as i16
+        };
+
+        let valid = p.s.is_empty()
+            && (1..=12).contains(&month)
+            && (1..=days_in_month(year, month)).contains(&day)
+            && hour < 24
+            && minute < 60
+            && second < 60;
+        if !valid {
+            return None;
+        }
+
+        let local = days_from_civil(year as i64, month, day) * SECS_PER_DAY
+            + (hour * 3600 + minute * 60 + second) as i64;
+        Inst::from_unix(local - offset_minutes as i64 * 60, nanos, offset_minutes)
+    }
+}
+
+/// Reads the fixed-width numbers of a timestamp
+struct Parser<'a> {
+    s: &'a [u8],
+}
+
+impl<'a> Parser<'a> {
+    fn eat(&mut self, b: u8) -> bool {
+        match self.s.first() {
+            Some(&c) if c == b => {
+                self.s = &self.s[1..];
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn digits(&mut self, n: usize) -> Option<u32> {
+        if self.s.len() < n || !self.s[..n].iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        let value = self.s[..n]
+            .iter()
+            .fold(0, |acc, &d| acc * 10 + (d - b'0') as u32);
+        self.s = &self.s[n..];
+        Some(value)
+    }
+
+    /// Digits after the decimal point, as nanoseconds, any beyond the ninth being dropped
+    fn fraction(&mut self) -> Option<u32> {
+        let len = self.s.iter().take_while(|b| b.is_ascii_digit()).count();
+        if len == 0 {
+            return None;
+        }
+        let mut nanos = 0;
+        for i in 0..9 {
+            let digit = self.s.get(i).filter(|_| i < len).map_or(0, |d| d - b'0');
+            nanos = nanos * 10 + digit as u32;
+        }
+        self.s = &self.s[len..];
+        Some(nanos)
+    }
+}
+
+fn is_leap_year(year: u32) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar,
+/// after Howard Hinnant's `days_from_civil`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Writes the timestamp as RFC 3339, in the offset it was read with and with
+/// as many fractional digits as needed
+impl fmt::Display for Inst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let local = self.secs + self.offset_minutes as i64 * 60;
+        let (year, month, day) = civil_from_days(local.div_euclid(SECS_PER_DAY));
+        let secs_of_day = local.rem_euclid(SECS_PER_DAY);
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60
+        )?;
+
+        if self.nanos != 0 {
+            let fraction = format!("{:09}", self.nanos);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+
+        match self.offset_minutes {
+            0 => f.write_str("Z"),
+            offset => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+/// The error converting between `Inst` and the timestamps of `chrono` or
+/// `time`, for values outside the range of the other type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstRangeError;
+
+impl fmt::Display for InstRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("timestamp out of range")
+    }
+}
+
+impl std::error::Error for InstRangeError {}
+
+impl FromStr for Inst {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Inst, Error> {
+        Inst::parse(s).ok_or_else(|| Error::syntax(ErrorCode::InvalidInst, 0, 0, 0))
+    }
+}
+
+/// Written as `#inst "..."`, other formats seeing the pair `("inst" "...")`
+impl Serialize for Inst {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TAGGED_TOKEN, &("inst", self.to_string()))
+    }
+}
+
+/// Read from `#inst "..."`, or from a plain RFC 3339 string
+impl<'de> Deserialize<'de> for Inst {
+    fn deserialize<D>(deserializer: D) -> Result<Inst, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(InstVisitor)
+    }
+}
+
+struct InstVisitor;
+
+impl<'de> Visitor<'de> for InstVisitor {
+    type Value = Inst;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an #inst timestamp")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Inst, E>
+    where
+        E: de::Error,
+    {
+        Inst::parse(v).ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(v), &self))
+    }
+
+    // `deserialize_any` hands out tagged literals as the pair `(tag value)`
+    fn visit_seq<A>(self, mut seq: A) -> Result<Inst, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let tag: String = match seq.next_element()? {
+            Some(tag) => tag,
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+        if tag != "inst" {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Str(&tag),
+                &"the tag inst",
+            ));
+        }
+        let text: String = match seq.next_element()? {
+            Some(text) => text,
+            None => return Err(de::Error::invalid_length(1, &self)),
+        };
+        self.visit_str(&text)
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use super::{Inst, InstRangeError};
+    use chrono::{DateTime, FixedOffset, Offset, TimeZone, Utc};
+    use std::convert::TryFrom;
+
+    impl<Tz: TimeZone> TryFrom<DateTime<Tz>> for Inst {
+        type Error = InstRangeError;
+
+        /// Offsets with seconds are truncated to whole minutes
+        fn try_from(dt: DateTime<Tz>) -> Result<Inst, InstRangeError> {
+            let offset = dt.offset().fix().local_minus_utc() / 60;
+            Inst::from_unix(dt.timestamp(), dt.timestamp_subsec_nanos(), offset as i16)
+                .ok_or(InstRangeError)
+        }
+    }
+
+    impl TryFrom<Inst> for DateTime<FixedOffset> {
+        type Error = InstRangeError;
+
+        fn try_from(inst: Inst) -> Result<DateTime<FixedOffset>, InstRangeError> {
+            let offset =
+                FixedOffset::east_opt(inst.offset_minutes as i32 * 60).ok_or(InstRangeError)?;
+            Ok(DateTime::<Utc>::try_from(inst)?.with_timezone(&offset))
+        }
+    }
+
+    impl TryFrom<Inst> for DateTime<Utc> {
+        type Error = InstRangeError;
+
+        fn try_from(inst: Inst) -> Result<DateTime<Utc>, InstRangeError> {
+            DateTime::from_timestamp(inst.secs, inst.nanos).ok_or(InstRangeError)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_impls {
+    use super::{Inst, InstRangeError};
+    use std::convert::TryFrom;
+    use time::{OffsetDateTime, UtcOffset};
+
+    impl TryFrom<OffsetDateTime> for Inst {
+        type Error = InstRangeError;
+
+        /// Offsets with seconds are truncated to whole minutes
+        fn try_from(dt: OffsetDateTime) -> Result<Inst, InstRangeError> {
+            let offset = dt.offset().whole_seconds() / 60;
+            Inst::from_unix(dt.unix_timestamp(), dt.nanosecond(), offset as i16)
+                .ok_or(InstRangeError)
+        }
+    }
+
+    impl TryFrom<Inst> for OffsetDateTime {
+        type Error = InstRangeError;
+
+        fn try_from(inst: Inst) -> Result<OffsetDateTime, InstRangeError> {
+            let offset = UtcOffset::from_whole_seconds(inst.offset_minutes as i32 * 60)
+                .map_err(|_| InstRangeError)?;
+            let nanos = inst.secs as i128 * 1_000_000_000 + inst.nanos as i128;
+            let utc =
+                OffsetDateTime::from_unix_timestamp_nanos(nanos).map_err(|_| InstRangeError)?;
+            Ok(utc.to_offset(offset))
+        }
+    }
+}
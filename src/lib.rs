@@ -1,10 +1,29 @@
+#[cfg(feature = "bignum")]
+pub mod bignum;
+mod collections;
 mod de;
 mod error;
+mod inst;
+mod read;
+mod readers;
+mod ser;
+#[cfg(feature = "uuid")]
+pub mod uuid;
 mod value;
 
-pub use crate::de::from_str;
-pub use crate::error::Error;
-pub use crate::value::{Symbol, Tagged, Value};
+pub use crate::collections::{list, set};
+pub use crate::de::{
+    from_reader, from_slice, from_str, from_str_lenient, Deserializer, StreamDeserializer,
+};
+pub use crate::error::{Category, Error};
+pub use crate::inst::{Inst, InstRangeError};
+pub use crate::read::{IoRead, Read, SliceRead, StrRead};
+pub use crate::readers::{TagReaders, UnknownTag};
+pub use crate::ser::{
+    to_string, to_string_pretty, to_vec, to_vec_pretty, to_writer, to_writer_pretty,
+    CompactFormatter, Formatter, PrettyFormatter, Serializer,
+};
+pub use crate::value::{from_value, to_value, Symbol, Tagged, Value};
 
 #[macro_use]
 mod macros;
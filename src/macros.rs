@@ -87,7 +87,7 @@ macro_rules! edn_internal {
     };
 
     ($ns:tt/$symbol:tt) => {
-        edn!(@str_symbol concat!(stringify!($ns), "/", stringify!($symbol)));
+        edn!(@str_symbol concat!(stringify!($ns), "/", stringify!($symbol)))
     };
 
     (@str_symbol $symbol:expr) => {
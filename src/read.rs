@@ -0,0 +1,299 @@
+use std::io;
+use std::ops::Deref;
+
+use crate::error::Error;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// A source of edn text for the `Deserializer`
+///
+/// This trait is sealed and cannot be implemented outside of `serde_edn`. The
+/// implementations are `StrRead` and `SliceRead`, which let the deserializer
+/// borrow from the input, and `IoRead`, which pulls bytes from an `io::Read`.
+pub trait Read<'de>: private::Sealed {
+    #[doc(hidden)]
+    fn next(&mut self) -> Result<Option<u8>>;
+
+    #[doc(hidden)]
+    fn peek(&mut self) -> Result<Option<u8>>;
+
+    /// Consume the byte returned by the last `peek`
+    #[doc(hidden)]
+    fn discard(&mut self);
+
+    /// The position of the next byte to be read. Called for every token, so
+    /// readers may leave out the line and column if those are costly to track.
+    #[doc(hidden)]
+    fn position(&self) -> Position;
+
+    /// Complete a position returned by `position`, when reporting an error
+    #[doc(hidden)]
+    fn fill_position(&self, pos: Position) -> Position;
+
+    /// Read bytes for as long as `accept` holds, borrowing them from the input
+    /// when possible. Bytes already in `scratch` are kept in front of the new ones.
+    #[doc(hidden)]
+    fn read_while<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+        accept: fn(u8) -> bool,
+    ) -> Result<Reference<'de, 's, [u8]>>;
+}
+
+/// A location in the input, with one-based line and column. Columns count
+/// characters, not bytes.
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+/// Bytes either borrowed from the input or copied into a scratch buffer
+#[doc(hidden)]
+pub enum Reference<'b, 'c, T: ?Sized> {
+    Borrowed(&'b T),
+    Copied(&'c T),
+}
+
+impl<'b, 'c, T: ?Sized> Deref for Reference<'b, 'c, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match *self {
+            Reference::Borrowed(b) => b,
+            Reference::Copied(c) => c,
+        }
+    }
+}
+
+/// Reads edn from a byte slice
+pub struct SliceRead<'a> {
+    slice: &'a [u8],
+    index: usize,
+}
+
+/// Reads edn from a `&str`
+pub struct StrRead<'a> {
+    delegate: SliceRead<'a>,
+}
+
+/// Reads edn from an `io::Read`, one byte at a time
+///
+/// Reading byte by byte from an unbuffered source such as a `File` or a
+/// `TcpStream` is slow, so wrap those in an `io::BufReader` first.
+pub struct IoRead<R>
+where
+    R: io::Read,
+{
+    iter: io::Bytes<R>,
+    peeked: Option<u8>,
+    line: usize,
+    column: usize,
+    offset: usize,
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+impl<'a> SliceRead<'a> {
+    pub fn new(slice: &'a [u8]) -> Self {
+        SliceRead { slice, index: 0 }
+    }
+}
+
+impl<'a> private::Sealed for SliceRead<'a> {}
+
+impl<'a> Read<'a> for SliceRead<'a> {
+    fn next(&mut self) -> Result<Option<u8>> {
+        let b = self.slice.get(self.index).cloned();
+        if b.is_some() {
+            self.index += 1;
+        }
+        Ok(b)
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        Ok(self.slice.get(self.index).cloned())
+    }
+
+    fn discard(&mut self) {
+        self.index += 1;
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            line: 0,
+            column: 0,
+            offset: self.index,
+        }
+    }
+
+    fn fill_position(&self, pos: Position) -> Position {
+        let before = &self.slice[..pos.offset];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        Position {
+            line: before.iter().filter(|&&b| b == b'\n').count() + 1,
+            column: before[line_start..]
+                .iter()
+                .filter(|&&b| !is_continuation(b))
+                .count()
+                + 1,
+            offset: pos.offset,
+        }
+    }
+
+    fn read_while<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+        accept: fn(u8) -> bool,
+    ) -> Result<Reference<'a, 's, [u8]>> {
+        let start = self.index;
+        while self.index < self.slice.len() && accept(self.slice[self.index]) {
+            self.index += 1;
+        }
+
+        let run = &self.slice[start..self.index];
+        if scratch.is_empty() {
+            Ok(Reference::Borrowed(run))
+        } else {
+            scratch.extend_from_slice(run);
+            Ok(Reference::Copied(scratch))
+        }
+    }
+}
+
+impl<'a> StrRead<'a> {
+    pub fn new(s: &'a str) -> Self {
+        StrRead {
+            delegate: SliceRead::new(s.as_bytes()),
+        }
+    }
+}
+
+impl<'a> private::Sealed for StrRead<'a> {}
+
+impl<'a> Read<'a> for StrRead<'a> {
+    fn next(&mut self) -> Result<Option<u8>> {
+        self.delegate.next()
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        self.delegate.peek()
+    }
+
+    fn discard(&mut self) {
+        self.delegate.discard()
+    }
+
+    fn position(&self) -> Position {
+        self.delegate.position()
+    }
+
+    fn fill_position(&self, pos: Position) -> Position {
+        self.delegate.fill_position(pos)
+    }
+
+    fn read_while<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+        accept: fn(u8) -> bool,
+    ) -> Result<Reference<'a, 's, [u8]>> {
+        self.delegate.read_while(scratch, accept)
+    }
+}
+
+impl<R> IoRead<R>
+where
+    R: io::Read,
+{
+    // buffering is left to the caller, see the type docs
+    #[allow(clippy::unbuffered_bytes)]
+    pub fn new(reader: R) -> Self {
+        IoRead {
+            iter: reader.bytes(),
+            peeked: None,
+            line: 1,
+            column: 0,
+            offset: 0,
+        }
+    }
+
+    fn advance(&mut self, b: u8) {
+        self.offset += 1;
+        if b == b'\n' {
+            self.line += 1;
+            self.column = 0;
+        } else if !is_continuation(b) {
+            self.column += 1;
+        }
+    }
+}
+
+impl<R> private::Sealed for IoRead<R> where R: io::Read {}
+
+impl<'de, R> Read<'de> for IoRead<R>
+where
+    R: io::Read,
+{
+    fn next(&mut self) -> Result<Option<u8>> {
+        let b = match self.peeked.take() {
+            Some(b) => Some(b),
+            None => self.iter.next().transpose().map_err(Error::io)?,
+        };
+        if let Some(b) = b {
+            self.advance(b);
+        }
+        Ok(b)
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        if self.peeked.is_none() {
+            self.peeked = self.iter.next().transpose().map_err(Error::io)?;
+        }
+        Ok(self.peeked)
+    }
+
+    fn discard(&mut self) {
+        if let Some(b) = self.peeked.take() {
+            self.advance(b);
+        }
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column + 1,
+            offset: self.offset,
+        }
+    }
+
+    fn fill_position(&self, pos: Position) -> Position {
+        pos
+    }
+
+    fn read_while<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+        accept: fn(u8) -> bool,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        while let Some(b) = self.peek()? {
+            if !accept(b) {
+                break;
+            }
+            self.discard();
+            scratch.push(b);
+        }
+        Ok(Reference::Copied(scratch))
+    }
+}
+
+/// Bytes after the first one in a multi-byte UTF-8 sequence
+fn is_continuation(b: u8) -> bool {
+    b & 0xC0 == 0x80
+}
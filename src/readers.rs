@@ -0,0 +1,136 @@
+//! Custom readers for tagged literals, like Clojure's `*data-readers*`
+//!
+//! By default `#my/tag value` is kept as `Tagged::User`. A `TagReaders`
+//! registry given to the `Deserializer` turns the literals of a tag into
+//! whatever its reader returns, and decides what happens to tags no reader is
+//! registered for:
+//!
+//! ```
+//! use serde_edn::{Deserializer, TagReaders, UnknownTag, Value};
+//! use serde::Deserialize;
+//!
+//! let readers = TagReaders::new()
+//!     .reader("money", |value| match value {
+//!         Value::Vector(v) if v.len() == 2 => Ok(v[0].clone()),
+//!         _ => Err("expected [amount currency]"),
+//!     })
+//!     .unknown(UnknownTag::Strip);
+//!
+//! let text = r#"[#money [10 "EUR"] #my/id 7]"#;
+//! let mut de = Deserializer::from_str(text).with_tag_readers(readers);
+//! let prices = Vec::<i64>::deserialize(&mut de).unwrap();
+//! assert_eq!(prices, vec![10, 7]);
+//! ```
+//!
+//! Readers see the value as parsed, nested tagged literals included, and the
+//! literal is replaced by what they return both in `Value`s and in typed
+//! fields. The built-in `#inst` and `#uuid`, as well as the tag of byte arrays,
+//! count as known tags, though a reader registered for them takes precedence.
+
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+use crate::error::ErrorCode;
+use crate::value::{to_value, Symbol, Tagged, Value};
+
+type Reader = dyn Fn(Value) -> Result<Value, String> + Send + Sync;
+
+/// What happens to tagged literals no reader is registered for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownTag {
+    /// Keep the literal as `Tagged::User`, the default
+    #[default]
+    Keep,
+    /// Fail, as the Clojure reader does
+    Error,
+    /// Drop the tag, reading only the value
+    Strip,
+}
+
+/// A registry of functions reading the values of tagged literals, keyed by tag
+#[derive(Clone, Default)]
+pub struct TagReaders {
+    readers: BTreeMap<String, Arc<Reader>>,
+    unknown: UnknownTag,
+}
+
+impl TagReaders {
+    /// An empty registry, keeping unknown tags
+    pub fn new() -> Self {
+        TagReaders::default()
+    }
+
+    /// Read `#tag value` into what `reader` returns for the value, its errors
+    /// failing deserialization. The tag is given without the leading `#`.
+    pub fn reader<F, E>(mut self, tag: &str, reader: F) -> Self
+    where
+        F: Fn(Value) -> Result<Value, E> + Send + Sync + 'static,
+        E: Display,
+    {
+        let reader = move |value| reader(value).map_err(|err| err.to_string());
+        self.readers.insert(tag.to_owned(), Arc::new(reader));
+        self
+    }
+
+    /// Same as `reader`, for functions returning any serializable type, which
+    /// is then read the way `to_value` writes it
+    pub fn typed_reader<F, T, E>(self, tag: &str, reader: F) -> Self
+    where
+        F: Fn(Value) -> Result<T, E> + Send + Sync + 'static,
+        T: Serialize,
+        E: Display,
+    {
+        self.reader(tag, move |value| match reader(value) {
+            Ok(typed) => to_value(&typed).map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        })
+    }
+
+    /// Set what happens to the tags without a reader
+    pub fn unknown(mut self, unknown: UnknownTag) -> Self {
+        self.unknown = unknown;
+        self
+    }
+
+    /// Whether the literals of `tag` read differently than with no registry
+    pub(crate) fn claims(&self, tag: &str, bytes_tag: &str) -> bool {
+        self.readers.contains_key(tag)
+            || (self.unknown != UnknownTag::Keep && !is_known(tag, bytes_tag))
+    }
+
+    /// Read the literal `#tag value`
+    pub(crate) fn read(
+        &self,
+        tag: Symbol,
+        value: Value,
+        bytes_tag: &str,
+    ) -> Result<Value, ErrorCode> {
+        if let Some(reader) = self.readers.get(tag.as_str()) {
+            return reader(value).map_err(|msg| ErrorCode::Message(msg.into_boxed_str()));
+        }
+        if is_known(tag.as_str(), bytes_tag) {
+            return Tagged::read(tag, value).map(Value::Tagged);
+        }
+        match self.unknown {
+            UnknownTag::Keep => Ok(Value::Tagged(Tagged::new(tag, value))),
+            UnknownTag::Error => Err(ErrorCode::UnknownTag(tag.inner.into_boxed_str())),
+            UnknownTag::Strip => Ok(value),
+        }
+    }
+}
+
+fn is_known(tag: &str, bytes_tag: &str) -> bool {
+    tag == "inst" || tag == "uuid" || tag == bytes_tag
+}
+
+impl fmt::Debug for TagReaders {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TagReaders")
+            .field("tags", &self.readers.keys().collect::<Vec<_>>())
+            .field("unknown", &self.unknown)
+            .finish()
+    }
+}
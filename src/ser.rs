@@ -0,0 +1,1334 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use serde::ser::{self, Serialize};
+use std::borrow::Cow;
+use std::io;
+
+use crate::de::is_keyword_name;
+use crate::error::Error;
+#[cfg(feature = "bignum")]
+use crate::value::{BIGDEC_TOKEN, BIGINT_TOKEN};
+use crate::value::{BYTES_TAG, KEYWORD_TOKEN, LIST_TOKEN, SET_TOKEN, SYMBOL_TOKEN, TAGGED_TOKEN};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// A structure for serializing Rust values into edn text
+pub struct Serializer<W, F = CompactFormatter> {
+    writer: W,
+    formatter: F,
+    // set while serializing the contents of one of `Value`'s private newtype
+    // structs, telling the next call which edn construct it is writing
+    token: Option<Token>,
+    // the tag byte arrays are written with, as base64 strings
+    bytes_tag: Cow<'static, str>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Token {
+    Keyword,
+    Symbol,
+    Set,
+    List,
+    Tagged,
+    Tag,
+    #[cfg(feature = "bignum")]
+    BigInt,
+    #[cfg(feature = "bignum")]
+    BigDecimal,
+}
+
+impl<W> Serializer<W>
+where
+    W: io::Write,
+{
+    /// Create a serializer producing compact, single-line edn
+    pub fn new(writer: W) -> Self {
+        Serializer::with_formatter(writer, CompactFormatter)
+    }
+}
+
+impl<W> Serializer<W, PrettyFormatter>
+where
+    W: io::Write,
+{
+    /// Create a serializer laying out its output with a default `PrettyFormatter`
+    pub fn pretty(writer: W) -> Self {
+        Serializer::with_formatter(writer, PrettyFormatter::new())
+    }
+}
+
+impl<W, F> Serializer<W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    /// Create a serializer laying out its output with the given formatter
+    pub fn with_formatter(writer: W, formatter: F) -> Self {
+        Serializer {
+            writer,
+            formatter,
+            token: None,
+            bytes_tag: Cow::Borrowed(BYTES_TAG),
+        }
+    }
+
+    /// Write byte arrays as `#tag "..."` instead of the default `#base64 "..."`,
+    /// e.g. to match a reader tag registered on the Clojure side. `to_value`
+    /// and `from_value` always use `#base64`.
+    pub fn with_bytes_tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.bytes_tag = tag.into();
+        self
+    }
+
+    /// Unwrap the `Writer` from the `Serializer`
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Run one of the formatter hooks against the writer
+    fn format<H>(&mut self, hook: H) -> Result<()>
+    where
+        H: FnOnce(&mut F, &mut W) -> io::Result<()>,
+    {
+        hook(&mut self.formatter, &mut self.writer).map_err(Error::io)
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<()> {
+        self.format(|f, w| f.begin_string(w))?;
+
+        let mut start = 0;
+        for (i, c) in value.char_indices() {
+            if !needs_escape(c) {
+                continue;
+            }
+
+            if start < i {
+                self.format(|f, w| f.write_string_fragment(w, &value[start..i]))?;
+            }
+            self.format(|f, w| f.write_char_escape(w, c))?;
+            start = i + c.len_utf8();
+        }
+
+        if start < value.len() {
+            self.format(|f, w| f.write_string_fragment(w, &value[start..]))?;
+        }
+        self.format(|f, w| f.end_string(w))
+    }
+
+    fn begin(&mut self, kind: Collection) -> Result<()> {
+        self.format(|f, w| match kind {
+            Collection::Vector => f.begin_vector(w),
+            Collection::List => f.begin_list(w),
+            Collection::Set => f.begin_set(w),
+            Collection::Map => f.begin_map(w),
+            // the tag itself is written once it is serialized
+            Collection::Tagged => Ok(()),
+        })
+    }
+
+    fn end(&mut self, kind: Collection) -> Result<()> {
+        self.format(|f, w| match kind {
+            Collection::Vector => f.end_vector(w),
+            Collection::List => f.end_list(w),
+            Collection::Set => f.end_set(w),
+            Collection::Map => f.end_map(w),
+            Collection::Tagged => f.end_tagged(w),
+        })
+    }
+
+    /// Open the single-entry map `{:variant ...}` holding a non-unit enum variant
+    fn begin_variant(&mut self, variant: &'static str) -> Result<()> {
+        self.begin(Collection::Map)?;
+        self.format(|f, w| f.begin_map_key(w, true))?;
+        self.write_name(variant)?;
+        self.format(|f, w| f.end_map_key(w))?;
+        self.format(|f, w| f.begin_map_value(w))
+    }
+
+    /// Write a keyword, refusing names that would not read back as one, e.g.
+    /// fields renamed with `#[serde(rename = "a b")]`
+    fn write_name(&mut self, name: &str) -> Result<()> {
+        if !is_keyword_name(name) {
+            return Err(ser::Error::custom(format_args!(
+                "`{}` cannot be written as a keyword",
+                name
+            )));
+        }
+        self.format(|f, w| f.write_keyword(w, name))
+    }
+
+    fn end_variant(&mut self) -> Result<()> {
+        self.format(|f, w| f.end_map_value(w))?;
+        self.end(Collection::Map)
+    }
+}
+
+fn needs_escape(c: char) -> bool {
+    c == '"' || c == '\\' || c.is_control()
+}
+
+impl<'a, W, F> ser::Serializer for &'a mut Serializer<W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Compound<'a, W, F>;
+    type SerializeTuple = Compound<'a, W, F>;
+    type SerializeTupleStruct = Compound<'a, W, F>;
+    type SerializeTupleVariant = Compound<'a, W, F>;
+    type SerializeMap = Compound<'a, W, F>;
+    type SerializeStruct = Compound<'a, W, F>;
+    type SerializeStructVariant = Compound<'a, W, F>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.format(|f, w| f.write_bool(w, v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.format(|f, w| f.write_i64(w, v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.format(|f, w| f.write_u64(w, v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.format(|f, w| f.write_i128(w, v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.format(|f, w| f.write_u128(w, v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.format(|f, w| f.write_f32(w, v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.format(|f, w| f.write_f64(w, v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.format(|f, w| f.write_char(w, v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        match self.token.take() {
+            Some(Token::Keyword) => self.write_name(v),
+            Some(Token::Symbol) => self.format(|f, w| f.write_symbol(w, v)),
+            Some(Token::Tag) => self.format(|f, w| f.begin_tagged(w, v)),
+            #[cfg(feature = "bignum")]
+            Some(Token::BigInt) => self.format(|f, w| f.write_big_integer(w, v)),
+            #[cfg(feature = "bignum")]
+            Some(Token::BigDecimal) => self.format(|f, w| f.write_big_decimal(w, v)),
+            _ => self.write_str(v),
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        let tag = self.bytes_tag.clone();
+        self.format(|f, w| f.begin_tagged(w, &tag))?;
+        self.write_str(&BASE64.encode(v))?;
+        self.format(|f, w| f.end_tagged(w))
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.format(|f, w| f.write_nil(w))
+    }
+
+    // written as `nil`, same as `()`
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.write_name(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.token = match name {
+            KEYWORD_TOKEN => Some(Token::Keyword),
+            SYMBOL_TOKEN => Some(Token::Symbol),
+            SET_TOKEN => Some(Token::Set),
+            LIST_TOKEN => Some(Token::List),
+            TAGGED_TOKEN => Some(Token::Tagged),
+            #[cfg(feature = "bignum")]
+            BIGINT_TOKEN => Some(Token::BigInt),
+            #[cfg(feature = "bignum")]
+            BIGDEC_TOKEN => Some(Token::BigDecimal),
+            _ => None,
+        };
+
+        let result = value.serialize(&mut *self);
+        self.token = None;
+        result
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.begin_variant(variant)?;
+        value.serialize(&mut *self)?;
+        self.end_variant()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        match self.token.take() {
+            Some(Token::Set) => Compound::begin(self, Collection::Set, false),
+            Some(Token::List) => Compound::begin(self, Collection::List, false),
+            _ => Compound::begin(self, Collection::Vector, false),
+        }
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        match self.token.take() {
+            Some(Token::Tagged) => Compound::begin(self, Collection::Tagged, false),
+            _ => Compound::begin(self, Collection::List, false),
+        }
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        self.begin_variant(variant)?;
+        Compound::begin(self, Collection::List, true)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Compound::begin(self, Collection::Map, false)
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.begin_variant(variant)?;
+        Compound::begin(self, Collection::Map, true)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Collection {
+    Vector,
+    List,
+    Set,
+    Map,
+    // the pair (tag value) of a tagged literal
+    Tagged,
+}
+
+/// Serialization state for collections, tracking the first element and
+/// whether an enclosing variant map must also be closed at the end
+pub struct Compound<'a, W, F> {
+    ser: &'a mut Serializer<W, F>,
+    kind: Collection,
+    first: bool,
+    variant: bool,
+}
+
+impl<'a, W, F> Compound<'a, W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    fn begin(ser: &'a mut Serializer<W, F>, kind: Collection, variant: bool) -> Result<Self> {
+        ser.begin(kind)?;
+        Ok(Compound {
+            ser,
+            kind,
+            first: true,
+            variant,
+        })
+    }
+
+    fn element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let first = std::mem::replace(&mut self.first, false);
+
+        if let Collection::Tagged = self.kind {
+            if first {
+                self.ser.token = Some(Token::Tag);
+            }
+            return value.serialize(&mut *self.ser);
+        }
+
+        self.ser.format(|f, w| f.begin_element(w, first))?;
+        value.serialize(&mut *self.ser)?;
+        self.ser.format(|f, w| f.end_element(w))
+    }
+
+    fn key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let first = std::mem::replace(&mut self.first, false);
+        self.ser.format(|f, w| f.begin_map_key(w, first))?;
+        key.serialize(&mut *self.ser)?;
+        self.ser.format(|f, w| f.end_map_key(w))
+    }
+
+    fn value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.ser.format(|f, w| f.begin_map_value(w))?;
+        value.serialize(&mut *self.ser)?;
+        self.ser.format(|f, w| f.end_map_value(w))
+    }
+
+    fn field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let first = std::mem::replace(&mut self.first, false);
+        self.ser.format(|f, w| f.begin_map_key(w, first))?;
+        self.ser.write_name(key)?;
+        self.ser.format(|f, w| f.end_map_key(w))?;
+        self.value(value)
+    }
+
+    fn finish(self) -> Result<()> {
+        self.ser.end(self.kind)?;
+        if self.variant {
+            self.ser.end_variant()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W, F> ser::SerializeSeq for Compound<'a, W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W, F> ser::SerializeTuple for Compound<'a, W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W, F> ser::SerializeTupleStruct for Compound<'a, W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W, F> ser::SerializeTupleVariant for Compound<'a, W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W, F> ser::SerializeMap for Compound<'a, W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.value(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W, F> ser::SerializeStruct for Compound<'a, W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W, F> ser::SerializeStructVariant for Compound<'a, W, F>
+where
+    W: io::Write,
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+/// Controls how a `Serializer` writes every edn construct
+///
+/// Each hook has a default implementation producing compact edn, so a
+/// formatter only needs to override the hooks it wants to change: separators,
+/// the spelling of keywords and symbols, coloring, and so on.
+///
+/// All default atom hooks funnel their output through `write_raw_fragment`,
+/// so a formatter that needs to intercept the text (e.g. to measure it)
+/// may override just that one.
+pub trait Formatter {
+    /// Write a piece of text verbatim
+    fn write_raw_fragment<W>(&mut self, writer: &mut W, fragment: &[u8]) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(fragment)
+    }
+
+    fn write_nil<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, b"nil")
+    }
+
+    fn write_bool<W>(&mut self, writer: &mut W, value: bool) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let s: &[u8] = if value { b"true" } else { b"false" };
+        self.write_raw_fragment(writer, s)
+    }
+
+    fn write_i64<W>(&mut self, writer: &mut W, value: i64) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, value.to_string().as_bytes())
+    }
+
+    fn write_u64<W>(&mut self, writer: &mut W, value: u64) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, value.to_string().as_bytes())
+    }
+
+    fn write_i128<W>(&mut self, writer: &mut W, value: i128) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, value.to_string().as_bytes())
+    }
+
+    fn write_u128<W>(&mut self, writer: &mut W, value: u128) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, value.to_string().as_bytes())
+    }
+
+    /// Write an arbitrary-precision integer, `digits` being given without the `N` suffix
+    fn write_big_integer<W>(&mut self, writer: &mut W, digits: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, digits.as_bytes())?;
+        self.write_raw_fragment(writer, b"N")
+    }
+
+    /// Write an exact decimal, `digits` being given without the `M` suffix
+    fn write_big_decimal<W>(&mut self, writer: &mut W, digits: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, digits.as_bytes())?;
+        self.write_raw_fragment(writer, b"M")
+    }
+
+    fn write_f32<W>(&mut self, writer: &mut W, value: f32) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        if value.is_finite() {
+            // `Debug` always keeps a decimal point or an exponent, so the
+            // output cannot be read back as an integer
+            self.write_raw_fragment(writer, format!("{:?}", value).as_bytes())
+        } else {
+            self.write_f64(writer, f64::from(value))
+        }
+    }
+
+    fn write_f64<W>(&mut self, writer: &mut W, value: f64) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        if value.is_nan() {
+            self.write_raw_fragment(writer, b"##NaN")
+        } else if value.is_infinite() {
+            let s: &[u8] = if value > 0.0 { b"##Inf" } else { b"##-Inf" };
+            self.write_raw_fragment(writer, s)
+        } else {
+            self.write_raw_fragment(writer, format!("{:?}", value).as_bytes())
+        }
+    }
+
+    fn write_char<W>(&mut self, writer: &mut W, value: char) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        match value {
+            '\n' => self.write_raw_fragment(writer, b"\\newline"),
+            '\r' => self.write_raw_fragment(writer, b"\\return"),
+            ' ' => self.write_raw_fragment(writer, b"\\space"),
+            '\t' => self.write_raw_fragment(writer, b"\\tab"),
+            c if c.is_control() => {
+                self.write_raw_fragment(writer, format!("\\u{:04x}", c as u32).as_bytes())
+            }
+            c => {
+                let mut buf = [b'\\'; 5];
+                let len = c.encode_utf8(&mut buf[1..]).len();
+                self.write_raw_fragment(writer, &buf[..=len])
+            }
+        }
+    }
+
+    /// Called before the contents of a string
+    fn begin_string<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, b"\"")
+    }
+
+    /// Called after the contents of a string
+    fn end_string<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, b"\"")
+    }
+
+    /// Write a part of a string which needs no escaping
+    fn write_string_fragment<W>(&mut self, writer: &mut W, fragment: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, fragment.as_bytes())
+    }
+
+    /// Write a quote, backslash or control character inside a string
+    fn write_char_escape<W>(&mut self, writer: &mut W, c: char) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        match c {
+            '"' => self.write_raw_fragment(writer, b"\\\""),
+            '\\' => self.write_raw_fragment(writer, b"\\\\"),
+            '\n' => self.write_raw_fragment(writer, b"\\n"),
+            '\r' => self.write_raw_fragment(writer, b"\\r"),
+            '\t' => self.write_raw_fragment(writer, b"\\t"),
+            c => self.write_raw_fragment(writer, format!("\\u{:04x}", c as u32).as_bytes()),
+        }
+    }
+
+    /// Write a keyword, `name` being given without the leading `:`
+    fn write_keyword<W>(&mut self, writer: &mut W, name: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, b":")?;
+        self.write_raw_fragment(writer, name.as_bytes())
+    }
+
+    fn write_symbol<W>(&mut self, writer: &mut W, name: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, name.as_bytes())
+    }
+
+    /// Called before the value of a tagged literal, `tag` being given without the leading `#`
+    fn begin_tagged<W>(&mut self, writer: &mut W, tag: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.write_raw_fragment(writer, b"#")?;
+        self.write_raw_fragment(writer, tag.as_bytes())?;
+        self.write_raw_fragment(writer, b" ")
+    }
+
+    /// Called after the value of a tagged literal
+    fn end_tagged<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        Ok(())
+    }
+
+    fn begin_vector<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"[")
+    }
+
+    fn end_vector<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"]")
+    }
+
+    fn begin_list<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"(")
+    }
+
+    fn end_list<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b")")
+    }
+
+    fn begin_set<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"#{")
+    }
+
+    fn end_set<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"}")
+    }
+
+    fn begin_map<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"{")
+    }
+
+    fn end_map<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"}")
+    }
+
+    /// Called before every element of a vector, list or set
+    fn begin_element<W>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b" ")
+        }
+    }
+
+    /// Called after every element of a vector, list or set
+    fn end_element<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        Ok(())
+    }
+
+    /// Called before every key of a map, i.e. between map entries
+    fn begin_map_key<W>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b" ")
+        }
+    }
+
+    /// Called after every key of a map
+    fn end_map_key<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        Ok(())
+    }
+
+    /// Called before every value of a map, i.e. between a key and its value
+    fn begin_map_value<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b" ")
+    }
+
+    /// Called after every value of a map
+    fn end_map_value<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        Ok(())
+    }
+}
+
+/// Writes everything on a single line, separated by single spaces
+#[derive(Clone, Debug)]
+pub struct CompactFormatter;
+
+impl Formatter for CompactFormatter {}
+
+/// Lays out collections over several lines once they no longer fit the line width
+///
+/// A collection that fits in the remaining width is written on one line.
+/// Otherwise its first element stays next to the opening delimiter and every
+/// following element (or key-value pair, for maps) starts on a new line,
+/// aligned after the opening delimiter as is customary in Clojure:
+///
+/// ```text
+/// {:name "serde_edn"
+///  :keywords ["edn" "serde" "clojure"]}
+/// ```
+///
+/// Since the layout of a collection depends on all of its contents, output is
+/// buffered until the outermost collection is complete.
+#[derive(Clone, Debug)]
+pub struct PrettyFormatter {
+    width: usize,
+    indent: Option<usize>,
+    align_values: bool,
+    stack: Vec<Group>,
+    // column reached by text written outside of any collection
+    column: usize,
+}
+
+#[derive(Clone, Debug)]
+enum Doc {
+    Text(Vec<u8>),
+    Group(Group),
+}
+
+#[derive(Clone, Debug)]
+struct Group {
+    open: Vec<u8>,
+    close: &'static [u8],
+    map: bool,
+    items: Vec<Doc>,
+    width: usize,
+}
+
+impl Default for PrettyFormatter {
+    fn default() -> Self {
+        PrettyFormatter::new()
+    }
+}
+
+impl PrettyFormatter {
+    /// A formatter for 80 columns, aligning elements after the opening delimiter
+    pub fn new() -> Self {
+        PrettyFormatter {
+            width: 80,
+            indent: None,
+            align_values: false,
+            stack: Vec::new(),
+            column: 0,
+        }
+    }
+
+    /// Set the number of columns collections may use before being broken up
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Indent elements on continuation lines by a fixed number of columns from
+    /// the opening delimiter, instead of aligning them after it
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = Some(indent);
+        self
+    }
+
+    /// Pad the keys of maps laid out over several lines so that their values
+    /// start on the same column
+    pub fn with_aligned_map_values(mut self) -> Self {
+        self.align_values = true;
+        self
+    }
+
+    fn begin_group(&mut self, open: &'static [u8], close: &'static [u8], map: bool) {
+        self.stack.push(Group {
+            open: open.to_vec(),
+            close,
+            map,
+            items: Vec::new(),
+            width: 0,
+        });
+    }
+
+    fn end_group<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut group = self.stack.pop().expect("unbalanced collection");
+
+        match self.stack.last_mut() {
+            Some(parent) => {
+                // anything already written for this element is a prefix of the collection
+                if let Some(Doc::Text(prefix)) = parent.items.last_mut() {
+                    prefix.extend_from_slice(&group.open);
+                    group.open = std::mem::take(prefix);
+                    parent.items.pop();
+                }
+                group.width = group_width(&group);
+                parent.items.push(Doc::Group(group));
+                Ok(())
+            }
+            None => {
+                group.width = group_width(&group);
+                self.column = self.render(writer, &Doc::Group(group), self.column)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn begin_item(&mut self) {
+        if let Some(group) = self.stack.last_mut() {
+            group.items.push(Doc::Text(Vec::new()));
+        }
+    }
+
+    /// Write `doc` starting at column `col`, returning the column it ends on
+    fn render<W>(&self, writer: &mut W, doc: &Doc, col: usize) -> io::Result<usize>
+    where
+        W: ?Sized + io::Write,
+    {
+        let group = match doc {
+            Doc::Text(text) => {
+                writer.write_all(text)?;
+                return Ok(col + text_width(text));
+            }
+            Doc::Group(group) => group,
+        };
+
+        if col + group.width <= self.width {
+            render_flat(writer, doc)?;
+            return Ok(col + group.width);
+        }
+
+        writer.write_all(&group.open)?;
+        let first_col = col + text_width(&group.open);
+        let indent_col = col + self.indent.unwrap_or_else(|| text_width(&group.open));
+
+        let (step, key_width) = if group.map {
+            let keys = group.items.iter().step_by(2);
+            let widest = keys.map(doc_width).max().unwrap_or(0);
+            (2, if self.align_values { widest } else { 0 })
+        } else {
+            (1, 0)
+        };
+
+        let mut end = first_col;
+        for (i, entry) in group.items.chunks(step).enumerate() {
+            let start = if i == 0 {
+                first_col
+            } else {
+                writer.write_all(b"\n")?;
+                write_spaces(writer, indent_col)?;
+                indent_col
+            };
+
+            end = self.render(writer, &entry[0], start)?;
+            if end < start + key_width {
+                write_spaces(writer, start + key_width - end)?;
+                end = start + key_width;
+            }
+            for value in &entry[1..] {
+                writer.write_all(b" ")?;
+                end = self.render(writer, value, end + 1)?;
+            }
+        }
+
+        writer.write_all(group.close)?;
+        Ok(end + group.close.len())
+    }
+}
+
+fn text_width(text: &[u8]) -> usize {
+    // count chars, not bytes, skipping UTF-8 continuation bytes
+    text.iter().filter(|&&b| b & 0xC0 != 0x80).count()
+}
+
+fn doc_width(doc: &Doc) -> usize {
+    match doc {
+        Doc::Text(text) => text_width(text),
+        Doc::Group(group) => group.width,
+    }
+}
+
+fn group_width(group: &Group) -> usize {
+    let items: usize = group.items.iter().map(doc_width).sum();
+    let spaces = group.items.len().saturating_sub(1);
+    text_width(&group.open) + items + spaces + group.close.len()
+}
+
+fn render_flat<W>(writer: &mut W, doc: &Doc) -> io::Result<()>
+where
+    W: ?Sized + io::Write,
+{
+    match doc {
+        Doc::Text(text) => writer.write_all(text),
+        Doc::Group(group) => {
+            writer.write_all(&group.open)?;
+            for (i, item) in group.items.iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b" ")?;
+                }
+                render_flat(writer, item)?;
+            }
+            writer.write_all(group.close)
+        }
+    }
+}
+
+fn write_spaces<W>(writer: &mut W, n: usize) -> io::Result<()>
+where
+    W: ?Sized + io::Write,
+{
+    const SPACES: &[u8] = &[b' '; 64];
+
+    let mut left = n;
+    while left > 0 {
+        let chunk = left.min(SPACES.len());
+        writer.write_all(&SPACES[..chunk])?;
+        left -= chunk;
+    }
+    Ok(())
+}
+
+impl Formatter for PrettyFormatter {
+    fn write_raw_fragment<W>(&mut self, writer: &mut W, fragment: &[u8]) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let group = match self.stack.last_mut() {
+            Some(group) => group,
+            None => {
+                self.column = match fragment.iter().rposition(|&b| b == b'\n') {
+                    Some(newline) => text_width(&fragment[newline + 1..]),
+                    None => self.column + text_width(fragment),
+                };
+                return writer.write_all(fragment);
+            }
+        };
+
+        match group.items.last_mut() {
+            Some(Doc::Text(text)) => text.extend_from_slice(fragment),
+            _ => group.items.push(Doc::Text(fragment.to_vec())),
+        }
+        Ok(())
+    }
+
+    fn begin_vector<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.begin_group(b"[", b"]", false);
+        Ok(())
+    }
+
+    fn end_vector<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.end_group(writer)
+    }
+
+    fn begin_list<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.begin_group(b"(", b")", false);
+        Ok(())
+    }
+
+    fn end_list<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.end_group(writer)
+    }
+
+    fn begin_set<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.begin_group(b"#{", b"}", false);
+        Ok(())
+    }
+
+    fn end_set<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.end_group(writer)
+    }
+
+    fn begin_map<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.begin_group(b"{", b"}", true);
+        Ok(())
+    }
+
+    fn end_map<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.end_group(writer)
+    }
+
+    fn begin_element<W>(&mut self, _writer: &mut W, _first: bool) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.begin_item();
+        Ok(())
+    }
+
+    fn begin_map_key<W>(&mut self, _writer: &mut W, _first: bool) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.begin_item();
+        Ok(())
+    }
+
+    fn begin_map_value<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        self.begin_item();
+        Ok(())
+    }
+}
+
+/// Serialize the given data structure as edn into the IO stream
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+{
+    let mut ser = Serializer::new(writer);
+    value.serialize(&mut ser)
+}
+
+/// Serialize the given data structure as an edn byte vector
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let mut writer = Vec::with_capacity(128);
+    to_writer(&mut writer, value)?;
+    Ok(writer)
+}
+
+/// Serialize the given data structure as a String of edn
+pub fn to_string<T>(value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let vec = to_vec(value)?;
+    // the serializer only ever writes valid UTF-8
+    Ok(String::from_utf8(vec).expect("serializer emitted invalid UTF-8"))
+}
+
+/// Serialize the given data structure as pretty-printed edn into the IO stream
+pub fn to_writer_pretty<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+{
+    let mut ser = Serializer::pretty(writer);
+    value.serialize(&mut ser)
+}
+
+/// Serialize the given data structure as a pretty-printed edn byte vector
+pub fn to_vec_pretty<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let mut writer = Vec::with_capacity(128);
+    to_writer_pretty(&mut writer, value)?;
+    Ok(writer)
+}
+
+/// Serialize the given data structure as a String of pretty-printed edn
+pub fn to_string_pretty<T>(value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let vec = to_vec_pretty(value)?;
+    // the serializer only ever writes valid UTF-8
+    Ok(String::from_utf8(vec).expect("serializer emitted invalid UTF-8"))
+}
@@ -0,0 +1,94 @@
+//! Serde helpers for `uuid::Uuid` fields as `#uuid` tagged literals
+//!
+//! `Uuid` serializes itself as a plain string, so fields name this module to
+//! be written as `#uuid "..."`:
+//!
+//! ```
+//! use serde_derive::{Deserialize, Serialize};
+//! use uuid::Uuid;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Entity {
+//!     #[serde(with = "serde_edn::uuid")]
+//!     id: Uuid,
+//! }
+//!
+//! let edn = r#"{:id #uuid "f81d4fae-7dec-11d0-a765-00a0c91e6bf6"}"#;
+//! let e: Entity = serde_edn::from_str(edn).unwrap();
+//! assert_eq!(e.id.to_string(), "f81d4fae-7dec-11d0-a765-00a0c91e6bf6");
+//! assert_eq!(serde_edn::to_string(&e).unwrap(), edn);
+//! ```
+//!
+//! Plain strings are accepted when reading. Other formats see the tag and the
+//! hyphenated string as a pair.
+
+use serde::de::{self, Deserializer, SeqAccess, Unexpected, Visitor};
+use serde::ser::Serializer;
+
+use std::fmt;
+
+use ::uuid::Uuid;
+
+use crate::value::{parse_uuid, uuid_string, Tagged, TAGGED_TOKEN};
+
+pub fn serialize<S>(value: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(TAGGED_TOKEN, &("uuid", uuid_string(value.as_u128())))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Uuid, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(UuidVisitor)
+}
+
+impl From<Uuid> for Tagged {
+    fn from(uuid: Uuid) -> Tagged {
+        Tagged::UUID(uuid.as_u128())
+    }
+}
+
+struct UuidVisitor;
+
+impl<'de> Visitor<'de> for UuidVisitor {
+    type Value = Uuid;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a #uuid literal")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Uuid, E>
+    where
+        E: de::Error,
+    {
+        match parse_uuid(v) {
+            Some(u) => Ok(Uuid::from_u128(u)),
+            None => Err(de::Error::invalid_value(Unexpected::Str(v), &self)),
+        }
+    }
+
+    // `deserialize_any` hands out tagged literals as the pair `(tag value)`
+    fn visit_seq<A>(self, mut seq: A) -> Result<Uuid, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let tag: String = match seq.next_element()? {
+            Some(tag) => tag,
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+        if tag != "uuid" {
+            return Err(de::Error::invalid_value(
+                Unexpected::Str(&tag),
+                &"the tag uuid",
+            ));
+        }
+        let text: String = match seq.next_element()? {
+            Some(text) => text,
+            None => return Err(de::Error::invalid_length(1, &self)),
+        };
+        self.visit_str(&text)
+    }
+}
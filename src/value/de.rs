@@ -1,23 +1,53 @@
-#![allow(warnings)]
-
+use crate::de::{decode_base64, unexpected_integer, visit_byte_seq, visit_integer};
 use crate::error::Error;
-use crate::value::Value;
+use crate::value::{Symbol, Tagged, Value};
+#[cfg(feature = "bignum")]
+use crate::value::{BIGDEC_TOKEN, BIGINT_TOKEN};
+use crate::value::{BYTES_TAG, KEYWORD_TOKEN, LIST_TOKEN, SET_TOKEN, SYMBOL_TOKEN, TAGGED_TOKEN};
+use crate::value::VALUE_TOKEN;
 
 use serde::de;
-use serde::de::{SeqAccess, Visitor};
+use serde::de::value::{BorrowedStrDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, EnumAccess, Expected, IntoDeserializer, MapAccess,
+    SeqAccess, Unexpected, VariantAccess, Visitor,
+};
 
 use serde::{Deserialize, Deserializer};
 
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
 impl<'de> Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Value, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_any(ValueVisitor)
+        deserializer.deserialize_newtype_struct(VALUE_TOKEN, ValueVisitor)
     }
 }
 
-//type Result<T> = std::result::Result<T, Error>;
+/// Interpret a `Value` as an instance of type `T`, the same way `from_str`
+/// would read the edn text of that value
+///
+/// Like `from_str`, this reads byte arrays only from `#base64` tagged literals;
+/// values holding a tag given to `with_bytes_tag` need to go through a
+/// `Deserializer` configured with it.
+pub fn from_value<T>(value: Value) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(value)
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
 
 macro_rules! deserialize_integer {
     ($method:ident, $visit_method:ident) => {
@@ -26,27 +56,60 @@ macro_rules! deserialize_integer {
             V: Visitor<'de>,
         {
             match self {
-                Value::Integer(i) => {
-                    let conv = num_traits::NumCast::from(i).ok_or(Error::NumericOutOfBounds)?;
-                    visitor.$visit_method(conv)
-                }
-                _ => Err(Error::Bad),
+                Value::Integer(i) => match num_traits::NumCast::from(i) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(unexpected_integer(i), &visitor)),
+                },
+                Value::U128(u) => match num_traits::NumCast::from(u) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Other("128-bit integer"),
+                        &visitor,
+                    )),
+                },
+                #[cfg(feature = "bignum")]
+                Value::BigInt(n) => match num_traits::NumCast::from(n) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Other("big integer"),
+                        &visitor,
+                    )),
+                },
+                other => Err(other.invalid_type(&visitor)),
             }
         }
-    }
+    };
 }
 
 macro_rules! deserialize_float {
     ($method:ident, $visit_method:ident) => {
-        fn $method<V>(self, _visitor: V) -> Result<V::Value, Error>
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
         where
             V: Visitor<'de>,
         {
-            unimplemented!()
+            match self {
+                Value::Float(f) => match num_traits::NumCast::from(f.into_inner()) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Float(f.into_inner()),
+                        &visitor,
+                    )),
+                },
+                #[cfg(feature = "bignum")]
+                Value::BigDecimal(d) => match num_traits::NumCast::from(d) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Other("big decimal"),
+                        &visitor,
+                    )),
+                },
+                other => Err(other.invalid_type(&visitor)),
+            }
         }
-    }
+    };
 }
 
+/// Reads a `Value` the way `serde_edn::Deserializer` reads the edn text it was parsed from
 impl<'de> Deserializer<'de> for Value {
     type Error = Error;
 
@@ -58,17 +121,26 @@ impl<'de> Deserializer<'de> for Value {
             Value::Nil => visitor.visit_unit(),
             Value::Bool(v) => visitor.visit_bool(v),
             Value::String(v) => visitor.visit_string(v),
-            //Value::Char(v) => visitor.visit_char(v),
-            //Value::Symbol(v) => visitor.visit(v),
-            //Value::Keyword(v) => visitor.visit(v),
-            Value::Integer(v) => visitor.visit_i64(v),
+            Value::Char(v) => visitor.visit_char(v),
+            Value::Symbol(v) => visitor.visit_string(v.inner),
+            Value::Keyword(v) => visitor.visit_string(v),
+            Value::Integer(v) => visit_integer(visitor, v),
+            Value::U128(u) => visitor.visit_u128(u),
             Value::Float(v) => visitor.visit_f64(v.into()),
-            //Value::List(v) => visitor.visit(v),
-            //Value::Vector(v) => visitor.visit(v),
-            //Value::Map(v) => visitor.visit(v),
-            //Value::Set(v) => visitor.visit(v),
-            //Value::Tagged(v) => visitor.visit(v),
-            other => panic!("unhandled case {:?}", other),
+            #[cfg(feature = "bignum")]
+            Value::BigInt(n) => private_entry(visitor, BIGINT_TOKEN, Value::String(n.to_string())),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(d) => {
+                private_entry(visitor, BIGDEC_TOKEN, Value::String(d.to_string()))
+            }
+            Value::List(v) | Value::Vector(v) => visit_array(v, visitor),
+            Value::Set(v) => visit_array(v.into_iter().collect(), visitor),
+            Value::Map(v) => visit_object(v, visitor),
+            // the pair `(tag value)`, as `Tagged` serializes for other formats
+            Value::Tagged(tagged) => {
+                let (tag, value) = tagged.into_pair();
+                visit_array(vec![Value::String(tag), value], visitor)
+            }
         }
     }
 
@@ -76,34 +148,42 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Bool(b) => visitor.visit_bool(b),
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
     deserialize_integer!(deserialize_i8, visit_i8);
     deserialize_integer!(deserialize_i16, visit_i16);
     deserialize_integer!(deserialize_i32, visit_i32);
     deserialize_integer!(deserialize_i64, visit_i64);
+    deserialize_integer!(deserialize_i128, visit_i128);
 
     deserialize_integer!(deserialize_u8, visit_u8);
     deserialize_integer!(deserialize_u16, visit_u16);
     deserialize_integer!(deserialize_u32, visit_u32);
     deserialize_integer!(deserialize_u64, visit_u64);
+    deserialize_integer!(deserialize_u128, visit_u128);
 
     deserialize_float!(deserialize_f32, visit_f32);
     deserialize_float!(deserialize_f64, visit_f64);
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value, Error>
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Char(c) => visitor.visit_char(c),
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        self.deserialize_string(visitor)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -112,87 +192,605 @@ impl<'de> Deserializer<'de> for Value {
     {
         match self {
             Value::String(s) => visitor.visit_string(s),
-            _ => Err(Error::Bad),
+            other => Err(other.invalid_type(&visitor)),
         }
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Error>
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        self.deserialize_byte_buf(visitor)
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Error>
+    /// Byte arrays are read from `#base64 "..."` or from a vector or list of integers
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Tagged(Tagged::User(ref tag, ref value)) if tag.as_str() == BYTES_TAG => {
+                visitor.visit_byte_buf(base64_bytes(value)?)
+            }
+            Value::List(v) | Value::Vector(v) => {
+                visitor.visit_byte_buf(Vec::<u8>::deserialize(Value::Vector(v))?)
+            }
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Nil => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
-    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            Value::List(ref v) | Value::Vector(ref v) if v.is_empty() => visitor.visit_unit(),
+            Value::Symbol(ref s) if s.as_str() == name => visitor.visit_unit(),
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
+    /// Newtype structs are read from the wrapped value itself, the way
+    /// `to_value` writes them
     fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        // requested by `serde_edn::bignum`, which parses the digits itself
+        #[cfg(feature = "bignum")]
+        if name == BIGINT_TOKEN || name == BIGDEC_TOKEN {
+            return match self {
+                Value::BigInt(n) => visitor.visit_string(n.to_string()),
+                Value::BigDecimal(d) => visitor.visit_string(d.to_string()),
+                Value::Integer(i) => visit_integer(visitor, i),
+                Value::U128(u) => visitor.visit_u128(u),
+                Value::Float(f) => visitor.visit_f64(f.into_inner()),
+                other => Err(other.invalid_type(&visitor)),
+            };
+        }
+
+        if name != VALUE_TOKEN {
+            return visitor.visit_newtype_struct(self);
+        }
+
+        // `Value` itself is asking, so keep what `deserialize_any` would lose
+        match self {
+            Value::Symbol(s) => private_entry(visitor, SYMBOL_TOKEN, Value::String(s.inner)),
+            Value::Keyword(k) => private_entry(visitor, KEYWORD_TOKEN, Value::String(k)),
+            Value::List(l) => private_entry(visitor, LIST_TOKEN, Value::Vector(l)),
+            Value::Set(s) => {
+                private_entry(visitor, SET_TOKEN, Value::Vector(s.into_iter().collect()))
+            }
+            Value::Tagged(tagged) => {
+                let (tag, value) = tagged.into_pair();
+                let pair = Value::Vector(vec![Value::String(tag), value]);
+                private_entry(visitor, TAGGED_TOKEN, pair)
+            }
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::List(v) | Value::Vector(v) => visit_array(v, visitor),
+            Value::Set(v) => visit_array(v.into_iter().collect(), visitor),
+            Value::Tagged(Tagged::User(ref tag, ref value)) if tag.as_str() == BYTES_TAG => {
+                visit_byte_seq(base64_bytes(value)?, visitor)
+            }
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::List(v) | Value::Vector(v) => visit_array(v, visitor),
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Map(m) => visit_object(m, visitor),
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    /// Unit variants are read from keywords or symbols, the others from
+    /// `{:variant value}`, `#ns/variant value` or `(:variant values...)`
+    fn deserialize_enum<V>(
         self,
         _name: &'static str,
+        _variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Keyword(name) => visitor.visit_enum(name.into_deserializer()),
+            Value::Symbol(name) => visitor.visit_enum(name.inner.into_deserializer()),
+            Value::Tagged(Tagged::User(tag, value)) => {
+                // the namespace of the tag is left out of the variant name
+                let name = Value::String(tag.name().to_owned());
+                visitor.visit_enum(Variant::new(name, VariantContent::Value(*value)))
+            }
+            Value::Map(m) => {
+                if m.len() != 1 {
+                    return Err(de::Error::invalid_length(
+                        m.len(),
+                        &"a map with a single key",
+                    ));
+                }
+                let (name, value) = m.into_iter().next().unwrap();
+                visitor.visit_enum(Variant::new(name, VariantContent::Value(value)))
+            }
+            Value::List(l) => {
+                let mut elements = l.into_iter();
+                let name = match elements.next() {
+                    Some(name) => name,
+                    None => {
+                        let expected = &"a list headed by the variant name";
+                        return Err(de::Error::invalid_length(0, expected));
+                    }
+                };
+                visitor.visit_enum(Variant::new(name, VariantContent::Rest(elements)))
+            }
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
-    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value, Error>
+    /// Struct fields and variant names, given as keywords, symbols or strings
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Keyword(s) | Value::String(s) => visitor.visit_string(s),
+            Value::Symbol(s) => visitor.visit_string(s.inner),
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        drop(self);
+        visitor.visit_unit()
+    }
+}
+
+/// What follows the name of an enum variant
+enum VariantContent {
+    /// The value of `{:variant value}` or `#ns/variant value`
+    Value(Value),
+    /// The rest of `(:variant values...)`
+    Rest(std::vec::IntoIter<Value>),
+}
+
+impl VariantContent {
+    /// The one value of the variant, from either form
+    fn single(self) -> Result<Value, Error> {
+        match self {
+            VariantContent::Value(value) => Ok(value),
+            VariantContent::Rest(mut rest) => match (rest.next(), rest.len()) {
+                (Some(value), 0) => Ok(value),
+                (first, len) => Err(de::Error::invalid_length(
+                    first.is_some() as usize + len,
+                    &"a single value after the variant name",
+                )),
+            },
+        }
+    }
+}
+
+/// Gives access to the name and contents of an enum variant
+struct Variant {
+    name: Value,
+    content: VariantContent,
+}
+
+impl Variant {
+    fn new(name: Value, content: VariantContent) -> Self {
+        Variant { name, content }
+    }
+}
+
+impl<'de> EnumAccess<'de> for Variant {
+    type Error = Error;
+    type Variant = VariantContent;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, VariantContent), Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.name)?;
+        Ok((variant, self.content))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantContent {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self {
+            // `(:variant)`, while the other forms need some value, e.g. `{:variant nil}`
+            VariantContent::Rest(rest) => match rest.len() {
+                0 => Ok(()),
+                len => Err(de::Error::invalid_length(
+                    len,
+                    &"nothing after the variant name",
+                )),
+            },
+            VariantContent::Value(value) => <()>::deserialize(value),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.single()?)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            VariantContent::Rest(rest) => visit_array(rest.collect(), visitor),
+            VariantContent::Value(value) => value.deserialize_seq(visitor),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.single()?.deserialize_map(visitor)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_integer_ref {
+    ($method:ident, $visit_method:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self {
+                Value::Integer(i) => match num_traits::NumCast::from(*i) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(unexpected_integer(*i), &visitor)),
+                },
+                Value::U128(u) => match num_traits::NumCast::from(*u) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Other("128-bit integer"),
+                        &visitor,
+                    )),
+                },
+                #[cfg(feature = "bignum")]
+                Value::BigInt(n) => match num_traits::NumCast::from(n.clone()) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Other("big integer"),
+                        &visitor,
+                    )),
+                },
+                other => Err(other.invalid_type(&visitor)),
+            }
+        }
+    }
+}
+
+macro_rules! deserialize_float_ref {
+    ($method:ident, $visit_method:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self {
+                Value::Float(f) => match num_traits::NumCast::from(f.into_inner()) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Float(f.into_inner()),
+                        &visitor,
+                    )),
+                },
+                #[cfg(feature = "bignum")]
+                Value::BigDecimal(d) => match num_traits::NumCast::from(d.clone()) {
+                    Some(conv) => visitor.$visit_method(conv),
+                    None => Err(de::Error::invalid_value(
+                        Unexpected::Other("big decimal"),
+                        &visitor,
+                    )),
+                },
+                other => Err(other.invalid_type(&visitor)),
+            }
+        }
+    }
+}
+
+/// Same as the owned `Value`, with strings, keywords and symbols borrowed from the value
+impl<'de> Deserializer<'de> for &'de Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            Value::Bool(v) => visitor.visit_bool(*v),
+            Value::String(v) | Value::Keyword(v) => visitor.visit_borrowed_str(v),
+            Value::Char(v) => visitor.visit_char(*v),
+            Value::Symbol(v) => visitor.visit_borrowed_str(&v.inner),
+            Value::Integer(v) => visit_integer(visitor, *v),
+            Value::U128(u) => visitor.visit_u128(*u),
+            Value::Float(v) => visitor.visit_f64(v.into_inner()),
+            #[cfg(feature = "bignum")]
+            Value::BigInt(n) => private_entry(visitor, BIGINT_TOKEN, Value::String(n.to_string())),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(d) => {
+                private_entry(visitor, BIGDEC_TOKEN, Value::String(d.to_string()))
+            }
+            Value::List(v) | Value::Vector(v) => visit_array_ref(v.iter(), visitor),
+            Value::Set(v) => visit_array_ref(v.iter(), visitor),
+            Value::Map(v) => visit_object_ref(v, visitor),
+            // the pair `(tag value)`, as `Tagged` serializes for other formats
+            Value::Tagged(Tagged::User(tag, value)) => {
+                visitor.visit_seq(TaggedPair::new(tag.as_str(), value))
+            }
+            Value::Tagged(other) => {
+                let (tag, value) = other.clone().into_pair();
+                visit_array(vec![Value::String(tag), value], visitor)
+            }
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Bool(b) => visitor.visit_bool(*b),
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    deserialize_integer_ref!(deserialize_i8, visit_i8);
+    deserialize_integer_ref!(deserialize_i16, visit_i16);
+    deserialize_integer_ref!(deserialize_i32, visit_i32);
+    deserialize_integer_ref!(deserialize_i64, visit_i64);
+    deserialize_integer_ref!(deserialize_i128, visit_i128);
+
+    deserialize_integer_ref!(deserialize_u8, visit_u8);
+    deserialize_integer_ref!(deserialize_u16, visit_u16);
+    deserialize_integer_ref!(deserialize_u32, visit_u32);
+    deserialize_integer_ref!(deserialize_u64, visit_u64);
+    deserialize_integer_ref!(deserialize_u128, visit_u128);
+
+    deserialize_float_ref!(deserialize_f32, visit_f32);
+    deserialize_float_ref!(deserialize_f64, visit_f64);
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Char(c) => visitor.visit_char(*c),
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::String(s) => visitor.visit_borrowed_str(s),
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    /// Byte arrays are read from `#base64 "..."` or from a vector or list of integers
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Tagged(Tagged::User(tag, value)) if tag.as_str() == BYTES_TAG => {
+                visitor.visit_byte_buf(base64_bytes(value)?)
+            }
+            Value::List(_) | Value::Vector(_) => {
+                visitor.visit_byte_buf(Vec::<u8>::deserialize(self)?)
+            }
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            Value::List(v) | Value::Vector(v) if v.is_empty() => visitor.visit_unit(),
+            Value::Symbol(s) if s.as_str() == name => visitor.visit_unit(),
+            other => Err(other.invalid_type(&visitor)),
+        }
+    }
+
+    /// Newtype structs are read from the wrapped value itself, the way
+    /// `to_value` writes them
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        // `Value` and `serde_edn::bignum` end up owning what they read anyway
+        #[cfg(feature = "bignum")]
+        let owned = name == VALUE_TOKEN || name == BIGINT_TOKEN || name == BIGDEC_TOKEN;
+        #[cfg(not(feature = "bignum"))]
+        let owned = name == VALUE_TOKEN;
+        if owned {
+            return self.clone().deserialize_newtype_struct(name, visitor);
+        }
+
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::List(v) | Value::Vector(v) => visit_array_ref(v.iter(), visitor),
+            Value::Set(v) => visit_array_ref(v.iter(), visitor),
+            Value::Tagged(Tagged::User(tag, value)) if tag.as_str() == BYTES_TAG => {
+                visit_byte_seq(base64_bytes(value)?, visitor)
+            }
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
     fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::List(v) | Value::Vector(v) => visit_array_ref(v.iter(), visitor),
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
     fn deserialize_tuple_struct<V>(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        self.deserialize_tuple(len, visitor)
     }
 
-    fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value, Error>
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Map(m) => visit_object_ref(m, visitor),
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
     fn deserialize_struct<V>(
@@ -204,9 +802,11 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        self.deserialize_map(visitor)
     }
 
+    /// Unit variants are read from keywords or symbols, the others from
+    /// `{:variant value}`, `#ns/variant value` or `(:variant values...)`
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
@@ -216,16 +816,40 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        match self {
+            Value::Keyword(name) => visitor.visit_enum(BorrowedStrDeserializer::new(name)),
+            Value::Symbol(name) => visitor.visit_enum(BorrowedStrDeserializer::new(name.as_str())),
+            Value::Tagged(Tagged::User(tag, value)) => {
+                // the namespace of the tag is left out of the variant name
+                let name = BorrowedStrDeserializer::new(tag.name());
+                visitor.visit_enum(VariantRef::new(name, VariantContentRef::Value(value)))
+            }
+            Value::Map(m) => {
+                if m.len() != 1 {
+                    return Err(de::Error::invalid_length(m.len(), &"a map with a single key"));
+                }
+                let (name, value) = m.iter().next().unwrap();
+                visitor.visit_enum(VariantRef::new(name, VariantContentRef::Value(value)))
+            }
+            Value::List(l) => match l.split_first() {
+                Some((name, rest)) => {
+                    visitor.visit_enum(VariantRef::new(name, VariantContentRef::Rest(rest)))
+                }
+                None => Err(de::Error::invalid_length(0, &"a list headed by the variant name")),
+            },
+            other => Err(other.invalid_type(&visitor)),
+        }
     }
 
+    /// Struct fields and variant names, given as keywords, symbols or strings
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
         match self {
-            Value::Keyword(s) => visitor.visit_str(&s),
-            _ => Err(Error::Bad),
+            Value::Keyword(s) | Value::String(s) => visitor.visit_borrowed_str(s),
+            Value::Symbol(s) => visitor.visit_borrowed_str(&s.inner),
+            other => Err(other.invalid_type(&visitor)),
         }
     }
 
@@ -233,7 +857,247 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        visitor.visit_unit()
+    }
+}
+
+/// What follows the name of an enum variant, borrowed from the value
+enum VariantContentRef<'de> {
+    /// The value of `{:variant value}` or `#ns/variant value`
+    Value(&'de Value),
+    /// The rest of `(:variant values...)`
+    Rest(&'de [Value]),
+}
+
+impl<'de> VariantContentRef<'de> {
+    /// The one value of the variant, from either form
+    fn single(self) -> Result<&'de Value, Error> {
+        match self {
+            VariantContentRef::Value(value) => Ok(value),
+            VariantContentRef::Rest([value]) => Ok(value),
+            VariantContentRef::Rest(rest) => Err(de::Error::invalid_length(
+                rest.len(),
+                &"a single value after the variant name",
+            )),
+        }
+    }
+}
+
+/// Gives access to the name and contents of an enum variant, the name
+/// deserializing from either a `&Value` or the tag of a tagged literal
+struct VariantRef<'de, N> {
+    name: N,
+    content: VariantContentRef<'de>,
+}
+
+impl<'de, N> VariantRef<'de, N> {
+    fn new(name: N, content: VariantContentRef<'de>) -> Self {
+        VariantRef { name, content }
+    }
+}
+
+impl<'de, N> EnumAccess<'de> for VariantRef<'de, N>
+where
+    N: Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+    type Variant = VariantContentRef<'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, VariantContentRef<'de>), Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.name)?;
+        Ok((variant, self.content))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantContentRef<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self {
+            // `(:variant)`, while the other forms need some value, e.g. `{:variant nil}`
+            VariantContentRef::Rest([]) => Ok(()),
+            VariantContentRef::Rest(rest) => Err(de::Error::invalid_length(
+                rest.len(),
+                &"nothing after the variant name",
+            )),
+            VariantContentRef::Value(value) => <()>::deserialize(value),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.single()?)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            VariantContentRef::Rest(rest) => visit_array_ref(rest.iter(), visitor),
+            VariantContentRef::Value(value) => value.deserialize_seq(visitor),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.single()?.deserialize_map(visitor)
+    }
+}
+
+/// Hands out a tagged literal as the pair `(tag value)`
+struct TaggedPair<'de> {
+    tag: Option<&'de str>,
+    value: Option<&'de Value>,
+}
+
+impl<'de> TaggedPair<'de> {
+    fn new(tag: &'de str, value: &'de Value) -> Self {
+        TaggedPair {
+            tag: Some(tag),
+            value: Some(value),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for TaggedPair<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(tag) = self.tag.take() {
+            return seed.deserialize(BorrowedStrDeserializer::new(tag)).map(Some);
+        }
+        match self.value.take() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.tag.is_some() as usize + self.value.is_some() as usize)
+    }
+}
+
+fn visit_object_ref<'de, V>(
+    object: &'de BTreeMap<Value, Value>,
+    visitor: V,
+) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let mut map = MapDeserializer::new(object.iter());
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+fn visit_array_ref<'de, I, V>(array: I, visitor: V) -> Result<V::Value, Error>
+where
+    I: Iterator<Item = &'de Value>,
+    V: Visitor<'de>,
+{
+    let mut seq = SeqDeserializer::new(array);
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+/// The bytes of a `#base64 "..."` literal, given its value
+fn base64_bytes(value: &Value) -> Result<Vec<u8>, Error> {
+    match value {
+        Value::String(s) => decode_base64(s),
+        other => Err(other.invalid_type(&"a base64 string")),
+    }
+}
+
+fn visit_object<'de, V>(object: BTreeMap<Value, Value>, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let mut map = MapDeserializer::new(object.into_iter());
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+fn visit_array<'de, V>(array: Vec<Value>, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let mut seq = SeqDeserializer::new(array.into_iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+/// Hands the visitor a map with the single entry `token` => `value`, which
+/// `ValueVisitor::visit_map` turns back into the construct it stands for
+fn private_entry<'de, V>(visitor: V, token: &'static str, value: Value) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let entry = std::iter::once((Value::String(token.to_owned()), value));
+    visit_private_map(visitor, MapDeserializer::new(entry))
+}
+
+thread_local! {
+    /// Set while a private entry is handed to a visitor, so that maps of other
+    /// formats keyed by the same strings stay maps
+    static PRIVATE_MAP: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Hands the visitor a map holding a private entry, see `private_entry`
+pub(crate) fn visit_private_map<'de, V, A>(visitor: V, map: A) -> Result<V::Value, A::Error>
+where
+    V: Visitor<'de>,
+    A: MapAccess<'de>,
+{
+    PRIVATE_MAP.with(|private| private.set(true));
+    let result = visitor.visit_map(map);
+    PRIVATE_MAP.with(|private| private.set(false));
+    result
+}
+
+impl Value {
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            Value::Nil => Unexpected::Other("nil"),
+            Value::Bool(b) => Unexpected::Bool(*b),
+            Value::String(s) => Unexpected::Str(s),
+            Value::Char(c) => Unexpected::Char(*c),
+            Value::Symbol(_) => Unexpected::Other("symbol"),
+            Value::Keyword(_) => Unexpected::Other("keyword"),
+            Value::Integer(i) => unexpected_integer(*i),
+            Value::U128(_) => Unexpected::Other("128-bit integer"),
+            Value::Float(f) => Unexpected::Float(f.into_inner()),
+            #[cfg(feature = "bignum")]
+            Value::BigInt(_) => Unexpected::Other("big integer"),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(_) => Unexpected::Other("big decimal"),
+            Value::List(_) => Unexpected::Other("list"),
+            Value::Vector(_) => Unexpected::Other("vector"),
+            Value::Map(_) => Unexpected::Map,
+            Value::Set(_) => Unexpected::Other("set"),
+            Value::Tagged(_) => Unexpected::Other("tagged literal"),
+        }
+    }
+
+    fn invalid_type<E: de::Error>(&self, exp: &dyn Expected) -> E {
+        de::Error::invalid_type(self.unexpected(), exp)
     }
 }
 
@@ -243,7 +1107,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     type Value = Value;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        formatter.write_str("the world to end")
+        formatter.write_str("any edn value")
     }
 
     fn visit_unit<E>(self) -> Result<Self::Value, E> {
@@ -267,13 +1131,48 @@ impl<'de> Visitor<'de> for ValueVisitor {
     }
 
     fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E> {
         Ok(Value::Integer(v))
     }
 
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E> {
+        match i128::try_from(v) {
+            Ok(i) => Ok(Value::Integer(i)),
+            Err(_) => Ok(Value::U128(v)),
+        }
+    }
+
     fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
         Ok(Value::Float(v.into()))
     }
 
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(Value::Nil)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Value::deserialize(deserializer)
+    }
+
+    // formats that don't know `VALUE_TOKEN` hand over the contents
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    // lists arrive as a private entry, any other sequence is a vector
     fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
     where
         A: SeqAccess<'de>,
@@ -284,6 +1183,63 @@ impl<'de> Visitor<'de> for ValueVisitor {
             v.push(e);
         }
 
-        Ok(Value::List(v))
+        Ok(Value::Vector(v))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        // the constructs serde has no type for arrive as a map with a single private key,
+        // see `private_entry` and `de::bignum_entry`
+        let private = PRIVATE_MAP.with(|private| private.replace(false));
+        let mut m = BTreeMap::new();
+
+        while let Some(key) = map.next_key::<Value>()? {
+            if let Value::String(ref k) = key {
+                if private && m.is_empty() {
+                    if let Some(value) = visit_private_entry(k, &mut map)? {
+                        return Ok(value);
+                    }
+                }
+            }
+
+            let value = map.next_value()?;
+            m.insert(key, value);
+        }
+
+        Ok(Value::Map(m))
     }
 }
+
+fn visit_private_entry<'de, A>(key: &str, map: &mut A) -> Result<Option<Value>, A::Error>
+where
+    A: MapAccess<'de>,
+{
+    let value = match key {
+        KEYWORD_TOKEN => Value::Keyword(map.next_value()?),
+        SYMBOL_TOKEN => Value::symbol(map.next_value::<String>()?),
+        LIST_TOKEN => Value::List(map.next_value()?),
+        SET_TOKEN => Value::Set(map.next_value::<Vec<Value>>()?.into_iter().collect()),
+        TAGGED_TOKEN => {
+            let (tag, value) = map.next_value()?;
+            Value::Tagged(Tagged::read(Symbol { inner: tag }, value).map_err(de::Error::custom)?)
+        }
+        #[cfg(feature = "bignum")]
+        BIGINT_TOKEN => {
+            let digits: String = map.next_value()?;
+            Value::BigInt(digits.parse().map_err(|_| {
+                de::Error::invalid_value(Unexpected::Str(&digits), &"a big integer")
+            })?)
+        }
+        #[cfg(feature = "bignum")]
+        BIGDEC_TOKEN => {
+            let digits: String = map.next_value()?;
+            Value::BigDecimal(digits.parse().map_err(|_| {
+                de::Error::invalid_value(Unexpected::Str(&digits), &"a big decimal")
+            })?)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
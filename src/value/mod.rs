@@ -1,6 +1,21 @@
 mod de;
+mod ser;
+
+pub use self::de::from_value;
+#[cfg(feature = "bignum")]
+pub(crate) use self::de::visit_private_map;
+pub use self::ser::to_value;
+
+use crate::error::{Error, ErrorCode};
+use crate::inst::Inst;
+use crate::ser::Serializer;
+
+use serde::Serialize;
 
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Value {
@@ -10,8 +25,17 @@ pub enum Value {
     Char(char),
     Symbol(Symbol),
     Keyword(String),
-    Integer(i64),
+    /// An integer without a suffix, i128 covering the whole range of u64 as well
+    Integer(i128),
+    /// An integer above `i128::MAX`, smaller ones always being `Integer`
+    U128(u128),
     Float(ordered_float::OrderedFloat<f64>),
+    /// An `N`-suffixed integer, or one beyond the range of `Integer` and `U128`
+    #[cfg(feature = "bignum")]
+    BigInt(num_bigint::BigInt),
+    /// An `M`-suffixed exact decimal
+    #[cfg(feature = "bignum")]
+    BigDecimal(bigdecimal::BigDecimal),
     List(Vec<Value>),
     Vector(Vec<Value>),
     Map(BTreeMap<Value, Value>),
@@ -19,12 +43,33 @@ pub enum Value {
     Tagged(Tagged),
 }
 
+// Names of the newtype structs through which `Value` passes the edn constructs
+// missing from the serde data model. serde_edn's own serializers recognize them
+// and write the construct back out, any other serializer sees the plain contents.
+pub(crate) const KEYWORD_TOKEN: &str = "$serde_edn::private::Keyword";
+pub(crate) const SYMBOL_TOKEN: &str = "$serde_edn::private::Symbol";
+pub(crate) const SET_TOKEN: &str = "$serde_edn::private::Set";
+pub(crate) const TAGGED_TOKEN: &str = "$serde_edn::private::Tagged";
+// Written as lists by `serde_edn::list`, and naming them for `VALUE_TOKEN`
+pub(crate) const LIST_TOKEN: &str = "$serde_edn::private::List";
+// The newtype struct `Value` deserializes itself from. serde_edn's deserializers
+// answer it with single-entry maps keyed by one of the names above for the
+// constructs a plain `deserialize_any` would lose, others with the value itself.
+pub(crate) const VALUE_TOKEN: &str = "$serde_edn::private::Value";
+// The tag byte arrays are written with, unless the serializer is told otherwise
+pub(crate) const BYTES_TAG: &str = "base64";
+// these carry the digits of the number as a string
+#[cfg(feature = "bignum")]
+pub(crate) const BIGINT_TOKEN: &str = "$serde_edn::private::BigInt";
+#[cfg(feature = "bignum")]
+pub(crate) const BIGDEC_TOKEN: &str = "$serde_edn::private::BigDecimal";
+
 macro_rules! is_accessor {
     ($is_method:ident, $as_method:ident) => {
         pub fn $is_method(&self) -> bool {
             self.$as_method().is_some()
         }
-    }
+    };
 }
 
 macro_rules! is_as_accessor {
@@ -75,7 +120,10 @@ impl Value {
     }
 
     pub fn integer<I: num_traits::PrimInt>(i: I) -> Value {
-        Value::Integer(<i64 as num_traits::NumCast>::from(i).unwrap())
+        match <i128 as num_traits::NumCast>::from(i) {
+            Some(i) => Value::Integer(i),
+            None => Value::U128(<u128 as num_traits::NumCast>::from(i).unwrap()),
+        }
     }
 
     pub fn float<F: num_traits::Float>(f: F) -> Value {
@@ -106,8 +154,24 @@ impl Value {
     is_as_accessor!(is_map, as_map, as_map_mut, Map, BTreeMap<Value, Value>);
     is_as_accessor!(is_set, as_set, as_set_mut, Set, BTreeSet<Value>);
     is_as_accessor!(is_tagged, as_tagged, as_tagged_mut, Tagged, Tagged);
+    #[cfg(feature = "bignum")]
+    is_as_accessor!(
+        is_bigint,
+        as_bigint,
+        as_bigint_mut,
+        BigInt,
+        num_bigint::BigInt
+    );
+    #[cfg(feature = "bignum")]
+    is_as_accessor!(
+        is_bigdecimal,
+        as_bigdecimal,
+        as_bigdecimal_mut,
+        BigDecimal,
+        bigdecimal::BigDecimal
+    );
 
-    is_as_accessor_val!(is_integer, as_integer, as_integer_mut, Integer, i64);
+    is_as_accessor_val!(is_integer, as_integer, as_integer_mut, Integer, i128);
     is_as_accessor_val!(is_float, as_float, as_float_mut, Float, f64);
     is_as_accessor_val!(is_char, as_char, as_char_mut, Char, char);
     is_as_accessor_val!(is_bool, as_bool, as_bool_mut, Bool, bool);
@@ -120,11 +184,126 @@ pub struct Symbol {
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Tagged {
-    Inst(std::time::Instant),
+    /// `#inst "1985-04-12T23:20:50.52Z"`
+    Inst(Inst),
+    /// `#uuid "f81d4fae-7dec-11d0-a765-00a0c91e6bf6"`
     UUID(u128),
     User(Symbol, Box<Value>),
 }
 
+impl Tagged {
+    /// Build the tagged literal `#tag value`
+    pub fn new(tag: Symbol, value: Value) -> Tagged {
+        Tagged::User(tag, Box::new(value))
+    }
+
+    /// Build `#tag value` as read from edn, `#inst` and `#uuid` becoming their own variants
+    pub(crate) fn read(tag: Symbol, value: Value) -> Result<Tagged, ErrorCode> {
+        match (tag.as_str(), value) {
+            ("inst", Value::String(s)) => {
+                s.parse().map(Tagged::Inst).map_err(|_| ErrorCode::InvalidInst)
+            }
+            ("inst", _) => Err(ErrorCode::InvalidInst),
+            ("uuid", Value::String(s)) => {
+                parse_uuid(&s).map(Tagged::UUID).ok_or(ErrorCode::InvalidUuid)
+            }
+            ("uuid", _) => Err(ErrorCode::InvalidUuid),
+            (_, value) => Ok(Tagged::new(tag, value)),
+        }
+    }
+
+    /// The tag and the value it is written with, the inverse of `read`
+    pub(crate) fn into_pair(self) -> (String, Value) {
+        match self {
+            Tagged::Inst(inst) => ("inst".to_owned(), Value::String(inst.to_string())),
+            Tagged::UUID(u) => ("uuid".to_owned(), Value::String(uuid_string(u))),
+            Tagged::User(tag, value) => (tag.inner, *value),
+        }
+    }
+
+    /// The tag of the literal, without the leading `#`
+    pub fn tag(&self) -> &str {
+        match self {
+            Tagged::Inst(_) => "inst",
+            Tagged::UUID(_) => "uuid",
+            Tagged::User(tag, _) => tag.as_str(),
+        }
+    }
+}
+
+/// The hyphenated form of a UUID
+pub(crate) fn uuid_string(u: u128) -> String {
+    let hex = format!("{:032x}", u);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Parses the hyphenated form of a UUID, in either case
+pub(crate) fn parse_uuid(s: &str) -> Option<u128> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 36 {
+        return None;
+    }
+    let mut u = 0u128;
+    for (i, &b) in bytes.iter().enumerate() {
+        match i {
+            8 | 13 | 18 | 23 if b == b'-' => continue,
+            8 | 13 | 18 | 23 => return None,
+            _ => u = u << 4 | (b as char).to_digit(16)? as u128,
+        }
+    }
+    Some(u)
+}
+
+/// Writes the value as edn text, `{:#}` laying it out with the default `PrettyFormatter`
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct WriterFormatter<'a, 'b> {
+            inner: &'a mut fmt::Formatter<'b>,
+        }
+
+        impl<'a, 'b> io::Write for WriterFormatter<'a, 'b> {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                let s = std::str::from_utf8(buf)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                self.inner.write_str(s).map_err(io::Error::other)?;
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let alternate = f.alternate();
+        let writer = WriterFormatter { inner: f };
+        let result = if alternate {
+            self.serialize(&mut Serializer::pretty(writer))
+        } else {
+            self.serialize(&mut Serializer::new(writer))
+        };
+        result.map_err(|_| fmt::Error)
+    }
+}
+
+/// Parses a single edn form, keeping keywords, symbols, sets and tags as such
+impl FromStr for Value {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Value, Error> {
+        let mut de = crate::de::Deserializer::from_str(s);
+        let value = de.parse_value()?;
+        de.end()?;
+        Ok(value)
+    }
+}
+
 // TODO: these are just test implementations
 impl Symbol {
     pub fn as_str(&self) -> &str {
@@ -148,9 +327,9 @@ impl Symbol {
     }
 }
 
-impl Into<String> for Symbol {
-    fn into(self) -> String {
-        self.inner
+impl From<Symbol> for String {
+    fn from(s: Symbol) -> String {
+        s.inner
     }
 }
 
@@ -161,31 +340,3 @@ impl std::ops::Deref for Symbol {
         &self.inner
     }
 }
-
-use edn::Value as EValue;
-impl From<EValue> for Value {
-    fn from(e: EValue) -> Self {
-        match e {
-            EValue::Nil => Value::Nil,
-            EValue::Boolean(b) => Value::Bool(b),
-            EValue::String(s) => Value::String(s),
-            EValue::Char(c) => Value::Char(c),
-            EValue::Symbol(s) => Value::symbol(s),
-            EValue::Keyword(s) => Value::Keyword(s),
-            EValue::Integer(i) => Value::Integer(i),
-            EValue::Float(f) => Value::Float(f.into_inner().into()),
-            EValue::List(v) => Value::List(v.into_iter().map(Value::from).collect()),
-            EValue::Vector(v) => Value::Vector(v.into_iter().map(Value::from).collect()),
-            EValue::Map(m) => Value::Map(
-                m.into_iter()
-                    .map(|(k, v)| (Value::from(k), Value::from(v)))
-                    .collect(),
-            ),
-            EValue::Set(s) => Value::Set(s.into_iter().map(Value::from).collect()),
-            EValue::Tagged(s, val) => Value::Tagged(Tagged::User(
-                Symbol { inner: s },
-                Box::new(Value::from(*val)),
-            )),
-        }
-    }
-}
@@ -0,0 +1,487 @@
+use crate::error::Error;
+use crate::value::{uuid_string, Symbol, Tagged, Value};
+#[cfg(feature = "bignum")]
+use crate::value::{BIGDEC_TOKEN, BIGINT_TOKEN};
+use crate::value::{BYTES_TAG, KEYWORD_TOKEN, LIST_TOKEN, SET_TOKEN, SYMBOL_TOKEN, TAGGED_TOKEN};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use serde::ser::{self, Serialize, SerializeSeq};
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            Value::Nil => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Char(c) => serializer.serialize_char(*c),
+            Value::Symbol(s) => s.serialize(serializer),
+            Value::Keyword(k) => serializer.serialize_newtype_struct(KEYWORD_TOKEN, k),
+            // most serializers only take i128 when it is really needed
+            Value::Integer(i) => match i64::try_from(*i) {
+                Ok(i) => serializer.serialize_i64(i),
+                Err(_) => serializer.serialize_i128(*i),
+            },
+            Value::U128(u) => serializer.serialize_u128(*u),
+            Value::Float(f) => serializer.serialize_f64(f.into_inner()),
+            #[cfg(feature = "bignum")]
+            Value::BigInt(n) => serializer.serialize_newtype_struct(BIGINT_TOKEN, &n.to_string()),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(d) => {
+                serializer.serialize_newtype_struct(BIGDEC_TOKEN, &d.to_string())
+            }
+            Value::List(l) => {
+                use serde::ser::SerializeTuple;
+
+                let mut tuple = serializer.serialize_tuple(l.len())?;
+                for e in l {
+                    tuple.serialize_element(e)?;
+                }
+                tuple.end()
+            }
+            Value::Vector(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for e in v {
+                    seq.serialize_element(e)?;
+                }
+                seq.end()
+            }
+            Value::Map(m) => serializer.collect_map(m),
+            Value::Set(s) => serializer.serialize_newtype_struct(SET_TOKEN, s),
+            Value::Tagged(t) => t.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(SYMBOL_TOKEN, self.as_str())
+    }
+}
+
+// serializers other than serde_edn's see the pair `(tag value)`
+impl Serialize for Tagged {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            Tagged::Inst(inst) => inst.serialize(serializer),
+            Tagged::UUID(u) => {
+                serializer.serialize_newtype_struct(TAGGED_TOKEN, &(self.tag(), uuid_string(*u)))
+            }
+            Tagged::User(tag, value) => {
+                serializer.serialize_newtype_struct(TAGGED_TOKEN, &(tag.as_str(), value))
+            }
+        }
+    }
+}
+
+/// Convert a `T` into a `Value`, laid out the same way `to_string` writes it
+pub fn to_value<T>(value: &T) -> Result<Value, Error>
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(Serializer)
+}
+
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        Ok(Value::Integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        Ok(match i128::try_from(v) {
+            Ok(i) => Value::Integer(i),
+            Err(_) => Value::U128(v),
+        })
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(Value::Float(v.into()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::String(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        let tag = Symbol {
+            inner: BYTES_TAG.to_owned(),
+        };
+        Ok(Value::Tagged(Tagged::new(
+            tag,
+            Value::String(BASE64.encode(v)),
+        )))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(Value::Keyword(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let inner = value.serialize(self)?;
+
+        match (name, inner) {
+            (KEYWORD_TOKEN, Value::String(k)) => Ok(Value::Keyword(k)),
+            (SYMBOL_TOKEN, Value::String(s)) => Ok(Value::symbol(s)),
+            (SET_TOKEN, Value::Vector(v)) => Ok(Value::Set(v.into_iter().collect())),
+            (LIST_TOKEN, Value::Vector(v)) | (LIST_TOKEN, Value::List(v)) => Ok(Value::List(v)),
+            (TAGGED_TOKEN, Value::List(mut pair)) => match (pair.pop(), pair.pop()) {
+                (Some(value), Some(Value::String(tag))) if pair.is_empty() => {
+                    Tagged::read(Symbol { inner: tag }, value)
+                        .map(Value::Tagged)
+                        .map_err(ser::Error::custom)
+                }
+                _ => Err(ser::Error::custom("malformed tagged literal")),
+            },
+            #[cfg(feature = "bignum")]
+            (BIGINT_TOKEN, Value::String(n)) => n
+                .parse()
+                .map(Value::BigInt)
+                .map_err(|_| ser::Error::custom(format!("invalid big integer `{}`", n))),
+            #[cfg(feature = "bignum")]
+            (BIGDEC_TOKEN, Value::String(d)) => d
+                .parse()
+                .map(Value::BigDecimal)
+                .map_err(|_| ser::Error::custom(format!("invalid big decimal `{}`", d))),
+            (KEYWORD_TOKEN, _) | (SYMBOL_TOKEN, _) | (SET_TOKEN, _) | (LIST_TOKEN, _) => Err(
+                ser::Error::custom(format!("unexpected contents for {}", name)),
+            ),
+            (_, inner) => Ok(inner),
+        }
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(variant_map(variant, to_value(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, Error> {
+        Ok(SerializeVec {
+            vec: Vec::with_capacity(len.unwrap_or(0)),
+            list: false,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, Error> {
+        Ok(SerializeVec {
+            vec: Vec::with_capacity(len),
+            list: true,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeTupleVariant, Error> {
+        Ok(SerializeTupleVariant {
+            variant,
+            vec: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap {
+            map: BTreeMap::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeStructVariant, Error> {
+        Ok(SerializeStructVariant {
+            variant,
+            map: BTreeMap::new(),
+        })
+    }
+}
+
+/// The single-entry map `{:variant value}` holding a non-unit enum variant
+fn variant_map(variant: &str, value: Value) -> Value {
+    let mut map = BTreeMap::new();
+    map.insert(Value::Keyword(variant.to_owned()), value);
+    Value::Map(map)
+}
+
+struct SerializeVec {
+    vec: Vec<Value>,
+    list: bool,
+}
+
+struct SerializeTupleVariant {
+    variant: &'static str,
+    vec: Vec<Value>,
+}
+
+struct SerializeMap {
+    map: BTreeMap<Value, Value>,
+    next_key: Option<Value>,
+}
+
+struct SerializeStructVariant {
+    variant: &'static str,
+    map: BTreeMap<Value, Value>,
+}
+
+impl SerializeVec {
+    fn finish(self) -> Value {
+        if self.list {
+            Value::List(self.vec)
+        } else {
+            Value::Vector(self.vec)
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.vec.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.vec.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(variant_map(self.variant, Value::List(self.vec)))
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.next_key = Some(to_value(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .next_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.map.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Map(self.map))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.map
+            .insert(Value::Keyword(key.to_owned()), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.map
+            .insert(Value::Keyword(key.to_owned()), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(variant_map(self.variant, Value::Map(self.map)))
+    }
+}
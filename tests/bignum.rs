@@ -0,0 +1,169 @@
+#![cfg(feature = "bignum")]
+
+use bigdecimal::BigDecimal;
+use num_bigint::BigInt;
+use serde_derive::{Deserialize, Serialize};
+use serde_edn::{from_str, to_string, to_value, Value};
+
+use std::str::FromStr;
+
+fn big(s: &str) -> BigInt {
+    BigInt::from_str(s).unwrap()
+}
+
+fn dec(s: &str) -> BigDecimal {
+    BigDecimal::from_str(s).unwrap()
+}
+
+#[test]
+fn parse_values() {
+    assert_eq!("123N".parse::<Value>(), Ok(Value::BigInt(big("123"))));
+    assert_eq!(
+        "-98765432109876543210N".parse::<Value>(),
+        Ok(Value::BigInt(big("-98765432109876543210")))
+    );
+    assert_eq!("1.10M".parse::<Value>(), Ok(Value::BigDecimal(dec("1.10"))));
+    assert_eq!("+7M".parse::<Value>(), Ok(Value::BigDecimal(dec("7"))));
+    assert_eq!(
+        "2.5e3M".parse::<Value>(),
+        Ok(Value::BigDecimal(dec("2500")))
+    );
+
+    assert!("1.5N".parse::<Value>().is_err());
+    assert!("01N".parse::<Value>().is_err());
+
+    // plain numbers keep their machine types
+    assert_eq!("123".parse::<Value>(), Ok(Value::Integer(123)));
+    assert_eq!(
+        "340282366920938463463374607431768211455".parse::<Value>(),
+        Ok(Value::U128(u128::MAX))
+    );
+    // unless too large for them, becoming big integers as in Clojure
+    assert_eq!(
+        "340282366920938463463374607431768211456".parse::<Value>(),
+        Ok(Value::BigInt(big(
+            "340282366920938463463374607431768211456"
+        )))
+    );
+    assert_eq!(
+        "-170141183460469231731687303715884105729".parse::<Value>(),
+        Ok(Value::BigInt(big(
+            "-170141183460469231731687303715884105729"
+        )))
+    );
+    assert_eq!(
+        from_str::<u128>("340282366920938463463374607431768211456")
+            .unwrap_err()
+            .to_string(),
+        "invalid value: big integer, expected u128 at line 1 column 1"
+    );
+}
+
+#[test]
+fn display_round_trip() {
+    for text in &["123N", "-98765432109876543210N", "1.10M", "[1N 0.001M]"] {
+        let value = text.parse::<Value>().unwrap();
+        assert_eq!(value.to_string(), *text);
+    }
+
+    // the scale of a decimal is part of its value
+    let value = "1.10M".parse::<Value>().unwrap();
+    assert_eq!(
+        value.as_bigdecimal().map(|d| d.to_string()),
+        Some("1.10".into())
+    );
+}
+
+#[test]
+fn value_from_str_and_to_value() {
+    assert_eq!(from_str::<Value>("42N"), Ok(Value::BigInt(big("42"))));
+    assert_eq!(
+        from_str::<Value>("0.30M"),
+        Ok(Value::BigDecimal(dec("0.30")))
+    );
+
+    let value = Value::BigDecimal(dec("12.345"));
+    assert_eq!(to_value(&value), Ok(value));
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Payment {
+    #[serde(with = "serde_edn::bignum::big_decimal")]
+    amount: BigDecimal,
+    #[serde(with = "serde_edn::bignum::big_int")]
+    id: BigInt,
+}
+
+#[test]
+fn with_helpers() {
+    let payment = Payment {
+        amount: dec("19.990"),
+        id: big("340282366920938463463374607431768211456"),
+    };
+    let text = "{:amount 19.990M :id 340282366920938463463374607431768211456N}";
+
+    assert_eq!(to_string(&payment).unwrap(), text);
+    assert_eq!(from_str::<Payment>(text), Ok(payment));
+
+    assert_eq!(
+        from_str::<Payment>("{:amount 2.5 :id 7}"),
+        Ok(Payment {
+            amount: dec("2.5"),
+            id: big("7"),
+        })
+    );
+
+    assert_eq!(
+        to_value(&Payment {
+            amount: dec("1.5"),
+            id: big("2"),
+        })
+        .unwrap()
+        .to_string(),
+        "{:amount 1.5M :id 2N}"
+    );
+
+    let value: Value = text.parse().unwrap();
+    assert_eq!(
+        serde_edn::from_value::<Payment>(value),
+        from_str::<Payment>(text)
+    );
+
+    // plain integers beyond i64, and beyond u128
+    for id in &[
+        "-18446744073709551616",
+        "340282366920938463463374607431768211455",
+        "-170141183460469231731687303715884105729",
+    ] {
+        let text = format!("{{:amount 0 :id {}}}", id);
+        let expected = Payment {
+            amount: dec("0"),
+            id: big(id),
+        };
+        assert_eq!(from_str::<Payment>(&text), Ok(expected));
+        let value: Value = text.parse().unwrap();
+        assert_eq!(
+            serde_edn::from_value::<Payment>(value),
+            from_str::<Payment>(&text)
+        );
+    }
+
+    assert_eq!(
+        from_str::<Payment>("{:amount :x :id 1N}")
+            .unwrap_err()
+            .to_string(),
+        "invalid type: keyword, expected a decimal at line 1 column 10"
+    );
+}
+
+#[test]
+fn primitives_from_big_numbers() {
+    assert_eq!(from_str::<u64>("42N"), Ok(42));
+    assert_eq!(from_str::<f64>("0.5M"), Ok(0.5));
+    assert_eq!(
+        from_str::<i64>("98765432109876543210N")
+            .unwrap_err()
+            .to_string(),
+        "invalid value: big integer, expected i64 at line 1 column 1"
+    );
+}
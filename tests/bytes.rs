@@ -0,0 +1,107 @@
+use serde_derive::{Deserialize, Serialize};
+use serde_edn::{from_str, from_value, to_string, to_string_pretty, to_value, Deserializer, Value};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Signed {
+    #[serde(with = "serde_bytes")]
+    hash: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    signature: Vec<u8>,
+}
+
+fn signed() -> Signed {
+    Signed {
+        hash: vec![0xde, 0xad, 0xbe, 0xef],
+        signature: b"edn".to_vec(),
+    }
+}
+
+#[test]
+fn base64_round_trip() {
+    let text = r#"{:hash #base64 "3q2+7w==" :signature #base64 "ZWRu"}"#;
+    assert_eq!(to_string(&signed()).unwrap(), text);
+    assert_eq!(from_str::<Signed>(text), Ok(signed()));
+
+    let pretty = to_string_pretty(&signed()).unwrap();
+    assert_eq!(from_str::<Signed>(&pretty), Ok(signed()));
+
+    let value = to_value(&signed()).unwrap();
+    assert_eq!(value.to_string(), text);
+    assert_eq!(from_value::<Signed>(value.clone()), Ok(signed()));
+    assert_eq!(serde::Deserialize::deserialize(&value), Ok(signed()));
+}
+
+#[test]
+fn integers_are_accepted() {
+    let text = "{:hash [222 173 190 239] :signature (101 100 110)}";
+    assert_eq!(from_str::<Signed>(text), Ok(signed()));
+
+    let value: Value = text.parse().unwrap();
+    assert_eq!(from_value::<Signed>(value), Ok(signed()));
+
+    assert_eq!(
+        from_str::<Signed>("{:hash [1 256] :signature []}")
+            .unwrap_err()
+            .to_string(),
+        "invalid value: integer `256`, expected u8 at line 1 column 11"
+    );
+}
+
+#[test]
+fn plain_vec_reads_base64() {
+    assert_eq!(
+        from_str::<Vec<u8>>(r#"#base64 "3q2+7w==""#),
+        Ok(vec![0xde, 0xad, 0xbe, 0xef])
+    );
+    assert_eq!(from_str::<Vec<u8>>("[1 2]"), Ok(vec![1, 2]));
+}
+
+#[test]
+fn invalid_base64() {
+    assert_eq!(
+        from_str::<Signed>(r#"{:hash #base64 "not base64!" :signature []}"#)
+            .unwrap_err()
+            .to_string(),
+        "invalid value: string \"not base64!\", expected a base64 string at line 1 column 16"
+    );
+    assert_eq!(
+        from_str::<Signed>(r#"{:hash #base64 12 :signature []}"#)
+            .unwrap_err()
+            .to_string(),
+        "invalid type: integer `12`, expected a base64 string at line 1 column 16"
+    );
+    assert_eq!(
+        from_str::<Signed>(r#"{:hash #bytes "ZWRu" :signature []}"#)
+            .unwrap_err()
+            .to_string(),
+        "invalid type: tagged literal, expected byte array at line 1 column 8"
+    );
+}
+
+#[test]
+fn configurable_tag() {
+    use serde::Deserialize as _;
+
+    let mut out = Vec::new();
+    let mut ser = serde_edn::Serializer::new(&mut out).with_bytes_tag("my/bytes");
+    serde::Serialize::serialize(&signed(), &mut ser).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(
+        text,
+        r#"{:hash #my/bytes "3q2+7w==" :signature #my/bytes "ZWRu"}"#
+    );
+
+    let mut de = Deserializer::from_str(&text).with_bytes_tag("my/bytes");
+    assert_eq!(Signed::deserialize(&mut de), Ok(signed()));
+
+    // the default tag is then just another tagged literal
+    let mut de = Deserializer::from_str(r#"{:hash #base64 "ZWRu" :signature []}"#)
+        .with_bytes_tag(String::from("my/bytes"));
+    assert!(Signed::deserialize(&mut de).is_err());
+
+    // values only know the default tag
+    let value: Value = text.parse().unwrap();
+    assert!(from_value::<Signed>(value).is_err());
+    let value: Value = text.replace("#my/bytes", "#base64").parse().unwrap();
+    assert_eq!(from_value::<Signed>(value), Ok(signed()));
+}
@@ -1,19 +1,28 @@
+use serde::Deserialize as _;
 use serde_derive::Deserialize;
-use serde_edn::{from_str, Error};
+use serde_edn::{from_reader, from_slice, from_str, from_str_lenient, Deserializer, Error};
+use std::io;
 
 use maplit::{hashmap, hashset};
 
+fn err<T: std::fmt::Debug>(result: Result<T, Error>) -> String {
+    result.unwrap_err().to_string()
+}
+
 macro_rules! integer_test {
-    ($int:ty, $normal:expr, $overflow:expr) => {
+    ($int:ty, $normal:expr, $overflow:expr, $overflow_err:expr) => {
         let normal: $int = $normal;
         assert_eq!(from_str::<$int>(&normal.to_string()), Ok(normal));
         let over = $overflow;
+        assert_eq!(err(from_str::<$int>(&over.to_string())), $overflow_err);
+
         assert_eq!(
-            from_str::<$int>(&over.to_string()),
-            Err(Error::NumericOutOfBounds)
+            err(from_str::<$int>(":kw")),
+            format!(
+                "invalid type: keyword, expected {} at line 1 column 1",
+                stringify!($int)
+            )
         );
-
-        assert_eq!(from_str::<$int>(":kw"), Err(Error::Bad));
     };
 }
 
@@ -21,24 +30,98 @@ macro_rules! integer_test {
 fn bool() {
     assert_eq!(from_str::<bool>("true"), Ok(true));
     assert_eq!(from_str::<bool>("false"), Ok(false));
-    assert_eq!(from_str::<bool>("33"), Err(Error::Bad));
+    assert_eq!(
+        err(from_str::<bool>("33")),
+        "invalid type: integer `33`, expected a boolean at line 1 column 1"
+    );
 }
 
 #[test]
 fn integers() {
-    integer_test!(i8, 3, 128);
-    integer_test!(i16, 300, 2_usize.pow(15) + 1);
-    integer_test!(i32, 70_000, 2_usize.pow(31) + 1);
-
-    // FIXME disabled led due to parser bug
-    //integer_test!(i64, 2_i64.pow(33), 2_usize.pow(63) + 1);
-
-    integer_test!(u8, 3, 256);
-    integer_test!(u16, 300, 2_usize.pow(16) + 1);
-    integer_test!(u32, 70_000, 2_usize.pow(32) + 1);
-
-    // FIXME disabled led due to parser bug
-    //integer_test!(u64, 2_u64.pow(33), 2_usize.pow(63) + 1);
+    integer_test!(
+        i8,
+        3,
+        128,
+        "invalid value: integer `128`, expected i8 at line 1 column 1"
+    );
+    integer_test!(
+        i16,
+        300,
+        2_usize.pow(15) + 1,
+        "invalid value: integer `32769`, expected i16 at line 1 column 1"
+    );
+    integer_test!(
+        i32,
+        70_000,
+        2_usize.pow(31) + 1,
+        "invalid value: integer `2147483649`, expected i32 at line 1 column 1"
+    );
+
+    integer_test!(
+        i64,
+        2_i64.pow(33),
+        2_u64.pow(63) + 1,
+        "invalid value: integer `9223372036854775809`, expected i64 at line 1 column 1"
+    );
+    integer_test!(
+        i128,
+        -(2_i128.pow(100)),
+        2_u128.pow(127),
+        "invalid value: 128-bit integer, expected i128 at line 1 column 1"
+    );
+
+    integer_test!(
+        u8,
+        3,
+        256,
+        "invalid value: integer `256`, expected u8 at line 1 column 1"
+    );
+    integer_test!(
+        u16,
+        300,
+        2_usize.pow(16) + 1,
+        "invalid value: integer `65537`, expected u16 at line 1 column 1"
+    );
+    integer_test!(
+        u32,
+        70_000,
+        2_usize.pow(32) + 1,
+        "invalid value: integer `4294967297`, expected u32 at line 1 column 1"
+    );
+    integer_test!(
+        u64,
+        u64::MAX,
+        2_u128.pow(64),
+        "invalid value: 128-bit integer, expected u64 at line 1 column 1"
+    );
+    integer_test!(
+        u128,
+        u128::MAX,
+        "340282366920938463463374607431768211456",
+        // read as a big integer where there are big integers
+        if cfg!(feature = "bignum") {
+            "invalid value: big integer, expected u128 at line 1 column 1"
+        } else {
+            "number out of range at line 1 column 1"
+        }
+    );
+    assert_eq!(
+        from_str::<u128>(&serde_edn::to_string(&u128::MAX).unwrap()),
+        Ok(u128::MAX)
+    );
+    assert_eq!(
+        from_str::<u128>(&2_u128.pow(127).to_string()),
+        Ok(2_u128.pow(127))
+    );
+
+    assert_eq!(
+        err(from_str::<u64>("-1")),
+        "invalid value: integer `-1`, expected u64 at line 1 column 1"
+    );
+    assert_eq!(
+        err(from_str::<u128>("-1")),
+        "invalid value: integer `-1`, expected u128 at line 1 column 1"
+    );
 }
 
 #[test]
@@ -50,7 +133,10 @@ fn float() {
 #[test]
 fn char() {
     assert_eq!(from_str::<char>(r#"\c"#), Ok('c'));
-    assert_eq!(from_str::<char>(r#"\tababab"#), Err(Error::Bad));
+    assert_eq!(
+        err(from_str::<char>(r#"\tababab"#)),
+        "invalid character name at line 1 column 1"
+    );
 }
 
 #[test]
@@ -60,14 +146,40 @@ fn strings() {
 
     assert_eq!(parsed, Ok("astring".into()));
 
-    // str disabled due to parser limitations
-    /*
     let data = String::from(r#""astring""#);
     let middle: &str = data.as_str().trim_matches('"');
     let parsed = from_str::<&str>(data.as_str());
 
     assert_eq!(parsed, Ok(middle));
-    */
+
+    assert_eq!(
+        from_str::<String>(r#""tab\there \u00e9""#),
+        Ok("tab\there é".into())
+    );
+    assert_eq!(
+        err(from_str::<&str>(r#""a\nb""#)),
+        "invalid type: string \"a\\nb\", expected a borrowed string at line 1 column 1"
+    );
+}
+
+#[test]
+fn borrowed_strings() {
+    use std::borrow::Cow;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct S<'a> {
+        name: &'a str,
+        #[serde(borrow)]
+        note: Cow<'a, str>,
+    }
+
+    let input = r#"{:name "plain" :note "also plain"}"#;
+    let s = from_str::<S>(input).unwrap();
+    assert_eq!(s.name, "plain");
+    assert!(matches!(s.note, Cow::Borrowed("also plain")));
+
+    let s = from_slice::<S>(br#"{:name "plain" :note "line\nbreak"}"#).unwrap();
+    assert!(matches!(s.note, Cow::Owned(ref note) if note == "line\nbreak"));
 }
 
 #[test]
@@ -79,7 +191,10 @@ fn option() {
 #[test]
 fn unit() {
     assert_eq!(from_str::<()>("nil"), Ok(()));
-    assert_eq!(from_str::<()>("3"), Err(Error::Bad));
+    assert_eq!(
+        err(from_str::<()>("3")),
+        "invalid type: integer `3`, expected unit at line 1 column 1"
+    );
 }
 
 #[test]
@@ -89,7 +204,10 @@ fn tuple_from_list_or_vec() {
     assert_eq!(from_str::<Tup>(r#"(10 "abcd")"#), expected);
     assert_eq!(from_str::<Tup>(r#"[10 "abcd"]"#), expected);
 
-    assert_eq!(from_str::<Tup>(r#"[10 "abcd" 3]"#), Err(Error::Bad));
+    assert_eq!(
+        err(from_str::<Tup>(r#"[10 "abcd" 3]"#)),
+        "too many elements in collection at line 1 column 12"
+    );
 }
 
 #[test]
@@ -101,7 +219,10 @@ fn tuple_struct_from_list_or_vec() {
     assert_eq!(from_str::<Tup>(r#"(10 "abcd")"#), expected);
     assert_eq!(from_str::<Tup>(r#"[10 "abcd"]"#), expected);
 
-    assert_eq!(from_str::<Tup>(r#"[10 "abcd" 3]"#), Err(Error::Bad));
+    assert_eq!(
+        err(from_str::<Tup>(r#"[10 "abcd" 3]"#)),
+        "too many elements in collection at line 1 column 12"
+    );
 }
 
 #[test]
@@ -111,10 +232,17 @@ fn newtype_struct() {
 
     let expected = Ok(Tup(10));
     assert_eq!(from_str::<Tup>(r#"10"#), expected);
-    assert_eq!(from_str::<Tup>(r#"(10)"#), expected);
-    assert_eq!(from_str::<Tup>(r#"[10]"#), expected);
 
-    assert_eq!(from_str::<Tup>(r#"[10 "abcd" 3]"#), Err(Error::Bad));
+    // newtypes are transparent, so a collection is the wrapped value's
+    assert_eq!(
+        err(from_str::<Tup>(r#"[10]"#)),
+        "invalid type: vector, expected i32 at line 1 column 1"
+    );
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Wrapped(Vec<i32>);
+    assert_eq!(from_str::<Wrapped>("[1 2 3]"), Ok(Wrapped(vec![1, 2, 3])));
+    assert_eq!(from_str::<Wrapped>("(1)"), Ok(Wrapped(vec![1])));
 }
 
 #[test]
@@ -123,13 +251,14 @@ fn unit_struct() {
     struct YewNit;
 
     let expected = Ok(YewNit);
+    assert_eq!(from_str::<YewNit>(r#"nil"#), expected);
     assert_eq!(from_str::<YewNit>(r#"()"#), expected);
     assert_eq!(from_str::<YewNit>(r#"[]"#), expected);
     assert_eq!(from_str::<YewNit>(r#"YewNit"#), expected);
 
-    let err = Err(Error::Bad);
-    assert_eq!(from_str::<YewNit>(r#"[10]"#), err);
-    assert_eq!(from_str::<YewNit>(r#"(10)"#), err);
+    let expected = "too many elements in collection at line 1 column 2";
+    assert_eq!(err(from_str::<YewNit>(r#"[10]"#)), expected);
+    assert_eq!(err(from_str::<YewNit>(r#"(10)"#)), expected);
 }
 
 #[test]
@@ -185,4 +314,645 @@ fn struct_from_map() {
         b: "abc".into(),
     });
     assert_eq!(from_str::<S>(r#"{:b "abc" :a 74}"#), expected);
+    assert_eq!(from_str::<S>(r#"{b "abc" a 74}"#), expected);
+    assert_eq!(from_str::<S>(r#"{"b" "abc", "a" 74}"#), expected);
+    assert_eq!(from_str::<S>(r#"{:b "abc" a 74}"#), expected);
+}
+
+#[test]
+fn struct_field_identifiers() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct User {
+        #[serde(rename = "user/name")]
+        name: String,
+        #[serde(rename = "active?")]
+        active: bool,
+    }
+
+    let expected = Ok(User {
+        name: "ann".into(),
+        active: true,
+    });
+    assert_eq!(
+        from_str::<User>(r#"{:user/name "ann" :active? true}"#),
+        expected
+    );
+    assert_eq!(
+        from_str::<User>(r#"{user/name "ann" "active?" true}"#),
+        expected
+    );
+
+    assert_eq!(
+        err(from_str::<User>(
+            r#"{:user/name "ann" :active? true :admin? false}"#
+        )),
+        "unknown field `admin?`, expected `user/name` or `active?` at line 1 column 33"
+    );
+    assert_eq!(
+        err(from_str::<User>(r#"{:name "ann" :active? true}"#)),
+        "unknown field `name`, expected `user/name` or `active?` at line 1 column 2"
+    );
+    assert_eq!(
+        err(from_str::<User>(r#"{"user/name" "ann" 1 true}"#)),
+        "invalid type: integer `1`, expected field identifier at line 1 column 20"
+    );
+}
+
+#[test]
+fn error_position() {
+    #[derive(Debug, Deserialize)]
+    struct S {
+        #[allow(dead_code)]
+        a: u32,
+    }
+
+    let e = from_str::<Vec<S>>("[{:a 1}\n {:a 2}\n {:a \"x\"}]").unwrap_err();
+    assert_eq!((e.line(), e.column(), e.offset()), (3, 6, 21));
+    assert_eq!(
+        e.to_string(),
+        r#"invalid type: string "x", expected u32 at line 3 column 6"#
+    );
+    assert!(e.is_data());
+
+    let e = from_str::<Vec<u32>>("[1\n 2").unwrap_err();
+    assert_eq!((e.line(), e.column(), e.offset()), (2, 3, 5));
+    assert_eq!(
+        e.to_string(),
+        "EOF while parsing a vector at line 2 column 3"
+    );
+    assert!(e.is_eof());
+}
+
+#[test]
+fn struct_field_errors() {
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct S {
+        #[allow(dead_code)]
+        a: u32,
+    }
+
+    assert_eq!(
+        err(from_str::<S>("{}")),
+        "missing field `a` at line 1 column 2"
+    );
+    assert_eq!(
+        err(from_str::<S>("{:a 1 :b 2}")),
+        "unknown field `b`, expected `a` at line 1 column 7"
+    );
+    assert_eq!(
+        err(from_str::<(u8, u8)>("[1]")),
+        "invalid length 1, expected a tuple of size 2 at line 1 column 3"
+    );
+}
+
+#[test]
+fn from_bytes() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct S {
+        a: u32,
+        b: String,
+    }
+
+    let expected = S {
+        a: 74,
+        b: "ábc".into(),
+    };
+    let input = "{:b \"ábc\"\n :a 74}";
+    assert_eq!(from_slice::<S>(input.as_bytes()), Ok(expected));
+
+    let e = from_slice::<String>(b"\"a\xffb\"").unwrap_err();
+    assert_eq!(e.to_string(), "invalid UTF-8 at line 1 column 1");
+    assert!(e.is_syntax());
+
+    assert_eq!(
+        err(from_slice::<Vec<u32>>("[1\n é".as_bytes())),
+        "invalid type: symbol, expected u32 at line 2 column 2"
+    );
+}
+
+#[test]
+fn from_io_reader() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct S {
+        a: u32,
+        b: String,
+    }
+
+    let input = "{:b \"ábc\"\n :a 74}";
+    assert_eq!(
+        from_reader::<_, S>(input.as_bytes()),
+        Ok(S {
+            a: 74,
+            b: "ábc".into(),
+        })
+    );
+
+    let e = from_reader::<_, Vec<S>>("[{:a 1 :b \"é\"}\n {:a \"x\"}]".as_bytes()).unwrap_err();
+    assert_eq!((e.line(), e.column(), e.offset()), (2, 6, 21));
+    assert!(e.is_data());
+
+    assert_eq!(
+        err(from_reader::<_, String>(&b"\"a\xffb\""[..])),
+        "invalid UTF-8 at line 1 column 1"
+    );
+
+    struct Failing;
+
+    impl io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("broken pipe"))
+        }
+    }
+
+    let e = from_reader::<_, u32>(Failing).unwrap_err();
+    assert!(e.is_io());
+    assert_eq!(e.to_string(), "broken pipe at line 1 column 1");
+}
+
+#[test]
+fn stream() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Event {
+        id: u32,
+        kind: String,
+    }
+
+    let input = "{:id 1 :kind \"start\"}\n; a comment\n{:id 2, :kind \"stop\"} #_ignored ";
+    let mut stream = Deserializer::from_str(input).into_iter::<Event>();
+    assert_eq!(stream.byte_offset(), 0);
+    assert_eq!(
+        stream.next(),
+        Some(Ok(Event {
+            id: 1,
+            kind: "start".into()
+        }))
+    );
+    assert_eq!(stream.byte_offset(), 21);
+    assert_eq!(
+        stream.next(),
+        Some(Ok(Event {
+            id: 2,
+            kind: "stop".into()
+        }))
+    );
+    assert_eq!(stream.byte_offset(), 55);
+    assert_eq!(stream.next(), None);
+
+    let values: Vec<u32> = Deserializer::from_reader("1 2\n3".as_bytes())
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(values, vec![1, 2, 3]);
+
+    let mut stream = Deserializer::from_slice(b"1 :two 3").into_iter::<u32>();
+    assert_eq!(stream.next(), Some(Ok(1)));
+    assert_eq!(
+        err(stream.next().unwrap()),
+        "invalid type: keyword, expected u32 at line 1 column 3"
+    );
+    assert_eq!(stream.byte_offset(), 2);
+    assert_eq!(stream.next(), None);
+
+    assert_eq!(
+        Deserializer::from_str(" ;; nothing\n")
+            .into_iter::<u32>()
+            .next(),
+        None
+    );
+}
+
+#[test]
+fn trailing_input() {
+    let e = from_str::<u32>("1 2 garbage").unwrap_err();
+    assert_eq!(e.to_string(), "trailing characters at line 1 column 3");
+    assert_eq!(e.offset(), 2);
+    assert!(e.is_syntax());
+
+    assert_eq!(
+        err(from_slice::<Vec<u32>>(b"[1 2]\n[3]")),
+        "trailing characters at line 2 column 1"
+    );
+    assert_eq!(
+        err(from_reader::<_, u32>("1 )".as_bytes())),
+        "trailing characters at line 1 column 3"
+    );
+
+    assert_eq!(from_str::<u32>(" 1 ; done\n #_2 , "), Ok(1));
+
+    assert_eq!(from_str_lenient::<u32>("1 2 garbage"), Ok(1));
+    assert_eq!(
+        err(from_str_lenient::<u32>("")),
+        "EOF while parsing a value at line 1 column 1"
+    );
+
+    let mut de = Deserializer::from_str("[1] [2]");
+    assert_eq!(Vec::<u32>::deserialize(&mut de), Ok(vec![1]));
+    assert_eq!(err(de.end()), "trailing characters at line 1 column 5");
+}
+
+#[test]
+fn enums() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    enum Shape {
+        Point,
+        Circle { r: u32 },
+        Square(u32),
+        Rect(u32, u32),
+    }
+
+    type V = Vec<Shape>;
+
+    // unit variants
+    assert_eq!(
+        from_str::<V>("[:point point (:point) {:point nil} #geo/point nil]"),
+        Ok(vec![
+            Shape::Point,
+            Shape::Point,
+            Shape::Point,
+            Shape::Point,
+            Shape::Point
+        ])
+    );
+
+    // single-entry maps, as written by the serializer
+    assert_eq!(
+        from_str::<V>("[{:circle {:r 1}} {:square 2} {:rect [3 4]} {:rect (5 6)}]"),
+        Ok(vec![
+            Shape::Circle { r: 1 },
+            Shape::Square(2),
+            Shape::Rect(3, 4),
+            Shape::Rect(5, 6),
+        ])
+    );
+
+    // tagged literals
+    assert_eq!(
+        from_str::<V>("[#shape/circle {:r 1} #shape/square 2 #rect [3 4]]"),
+        Ok(vec![
+            Shape::Circle { r: 1 },
+            Shape::Square(2),
+            Shape::Rect(3, 4),
+        ])
+    );
+
+    // lists headed by a keyword
+    assert_eq!(
+        from_str::<V>("[(:circle {:r 1}) (:square 2) (:rect 3 4)]"),
+        Ok(vec![
+            Shape::Circle { r: 1 },
+            Shape::Square(2),
+            Shape::Rect(3, 4),
+        ])
+    );
+
+    assert_eq!(
+        err(from_str::<Shape>(":hexagon")),
+        "unknown variant `hexagon`, expected one of `point`, `circle`, `square`, `rect` at line 1 column 1"
+    );
+    assert_eq!(
+        err(from_str::<Shape>("{:square 1 :rect [1 2]}")),
+        "too many elements in collection at line 1 column 12"
+    );
+    assert_eq!(
+        err(from_str::<Shape>("{}")),
+        "invalid length 0, expected a map with a single key at line 1 column 2"
+    );
+    assert_eq!(
+        err(from_str::<Shape>("(:rect 1 2 3)")),
+        "too many elements in collection at line 1 column 12"
+    );
+    assert_eq!(
+        err(from_str::<Shape>(":square")),
+        "invalid type: unit variant, expected newtype variant at line 1 column 1"
+    );
+    assert_eq!(
+        err(from_str::<Shape>("42")),
+        "invalid type: integer `42`, expected enum Shape at line 1 column 1"
+    );
+}
+
+#[test]
+fn unknown_fields_are_skipped() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Event {
+        id: u32,
+        kind: String,
+    }
+
+    let input = r#"{:meta {:source "clj" :tags #{:a :b} :nested {[1 2] (3 #{4})}}
+                    :id 7
+                    :at #inst "2019-06-01T00:00:00Z"
+                    :extra [\c 1.5 nil ##Inf sym ns/sym #_ignored #my/tag {:x 1}]
+                    :kind "start"
+                    :more 12345678901234567890123}"#;
+    assert_eq!(
+        from_str::<Event>(input),
+        Ok(Event {
+            id: 7,
+            kind: "start".into()
+        })
+    );
+
+    // skipped forms must still be well formed
+    assert_eq!(
+        err(from_str::<Event>(r#"{:id 1 :kind "x" :bad [1 2)}"#)),
+        "unexpected closing delimiter at line 1 column 27"
+    );
+    assert_eq!(
+        err(from_str::<Event>(r#"{:id 1 :kind "x" :bad {:k}}"#)),
+        "map key without a value at line 1 column 26"
+    );
+    assert_eq!(
+        err(from_str::<Event>(r#"{:id 1 :kind "x" :bad #{1"#)),
+        "EOF while parsing a set at line 1 column 26"
+    );
+}
+
+#[test]
+fn ignored_any() {
+    use serde::de::IgnoredAny;
+    use serde_edn::Value;
+
+    assert_eq!(
+        from_str::<IgnoredAny>("[1 {:a #{2}} #t (3)]"),
+        Ok(IgnoredAny)
+    );
+    let value: Value = "[1 {:a #{2}} #t (3)]".parse().unwrap();
+    assert_eq!(IgnoredAny::deserialize(value), Ok(IgnoredAny));
+}
+
+#[test]
+fn any_covers_every_form() {
+    use serde::de::IgnoredAny;
+    use serde_edn::Value;
+
+    for edn in &[
+        r#"\a"#, ":kw", "sym", "#tag 1", "#{1}", "(1)", "[1]", "{1 2}",
+    ] {
+        assert_eq!(from_str::<IgnoredAny>(edn), Ok(IgnoredAny), "{}", edn);
+    }
+
+    // keywords and symbols read as names, tagged literals as the pair (tag value)
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(untagged)]
+    enum Any {
+        Name(String),
+        Pair(String, i64),
+        Numbers(Vec<i64>),
+        Map(std::collections::BTreeMap<String, Value>),
+    }
+
+    assert_eq!(from_str("ns/sym"), Ok(Any::Name("ns/sym".into())));
+    assert_eq!(from_str(":kw"), Ok(Any::Name("kw".into())));
+    assert_eq!(from_str("#my/tag 7"), Ok(Any::Pair("my/tag".into(), 7)));
+    assert_eq!(from_str("#{3}"), Ok(Any::Numbers(vec![3])));
+    assert_eq!(from_str("(1 2)"), Ok(Any::Numbers(vec![1, 2])));
+    assert_eq!(
+        from_str("{:a [x]}"),
+        Ok(Any::Map(
+            vec![("a".into(), Value::Vector(vec![Value::string("x")]))]
+                .into_iter()
+                .collect()
+        ))
+    );
+    assert!(from_str::<Any>("nil").is_err());
+}
+
+#[test]
+fn flatten() {
+    use serde_edn::Value;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Page {
+        offset: u32,
+        limit: u32,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Query {
+        q: String,
+        #[serde(flatten)]
+        page: Page,
+        #[serde(flatten)]
+        rest: std::collections::BTreeMap<String, Value>,
+    }
+
+    assert_eq!(
+        from_str(r#"{:q "edn" :offset 10 :limit 5 :order "asc"}"#),
+        Ok(Query {
+            q: "edn".into(),
+            page: Page {
+                offset: 10,
+                limit: 5
+            },
+            rest: vec![("order".into(), Value::string("asc"))]
+                .into_iter()
+                .collect(),
+        })
+    );
+
+    // a known limitation: serde buffers flattened entries in its own data
+    // model, where keywords are strings and sets are sequences
+    let query: Query = from_str(r#"{:q "" :offset 0 :limit 0 :order :asc :ids #{1}}"#).unwrap();
+    assert_eq!(query.rest["order"], Value::string("asc"));
+    assert_eq!(query.rest["ids"], Value::Vector(vec![Value::integer(1)]));
+}
+
+#[test]
+fn maps_keyed_like_private_entries() {
+    use serde_edn::{from_value, Value};
+
+    // only this crate's deserializers hand out keywords, sets and the like
+    // as private entries, other maps are kept whatever their keys
+    let json = r#"{"$serde_edn::private::Keyword": "a"}"#;
+    let value = serde_json::from_str::<Value>(json).unwrap();
+    assert_eq!(
+        value,
+        Value::Map(
+            vec![(
+                Value::string("$serde_edn::private::Keyword"),
+                Value::string("a")
+            )]
+            .into_iter()
+            .collect()
+        )
+    );
+    assert_eq!(from_value::<Value>(value.clone()), Ok(value.clone()));
+    assert_eq!(Value::deserialize(&value), Ok(value.clone()));
+    assert_eq!(from_str::<Value>(&value.to_string()), Ok(value));
+}
+
+#[test]
+fn from_value() {
+    use serde_edn::{from_value, to_value, Value};
+
+    #[derive(Debug, PartialEq, Deserialize, serde_derive::Serialize)]
+    enum Shape {
+        Point,
+        Circle(f64),
+        Rect { w: u8, h: u8 },
+        Line(i32, i32),
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, serde_derive::Serialize)]
+    struct Meters(f32);
+
+    #[derive(Debug, PartialEq, Deserialize, serde_derive::Serialize)]
+    struct Plugin {
+        name: String,
+        version: Option<(u8, u8)>,
+        tags: std::collections::BTreeSet<char>,
+        shapes: Vec<Shape>,
+        size: Meters,
+        enabled: bool,
+    }
+
+    let envelope: Value = r#"{:kind :plugin
+                               :body {:name "draw"
+                                      :version nil
+                                      :tags #{\a \b}
+                                      :shapes [:Point {:Circle 1.5} #shape/Rect {:w 2 :h 3} (Line 4 -5)]
+                                      :size 2.5
+                                      :enabled true}}"#
+        .parse()
+        .unwrap();
+    let map = envelope.as_map().unwrap();
+    assert_eq!(
+        map[&Value::Keyword("kind".into())],
+        Value::Keyword("plugin".into())
+    );
+
+    let body = map[&Value::Keyword("body".into())].clone();
+    let plugin = Plugin {
+        name: "draw".into(),
+        version: None,
+        tags: hashset! {'a', 'b'}.into_iter().collect(),
+        shapes: vec![
+            Shape::Point,
+            Shape::Circle(1.5),
+            Shape::Rect { w: 2, h: 3 },
+            Shape::Line(4, -5),
+        ],
+        size: Meters(2.5),
+        enabled: true,
+    };
+    assert_eq!(from_value::<Plugin>(body), Ok(plugin));
+
+    let plugin = Plugin {
+        name: "x".into(),
+        version: Some((1, 2)),
+        tags: Default::default(),
+        shapes: vec![Shape::Line(0, 1)],
+        size: Meters(0.5),
+        enabled: false,
+    };
+    assert_eq!(from_value(to_value(&plugin).unwrap()), Ok(plugin));
+
+    #[derive(Debug, PartialEq, Deserialize, serde_derive::Serialize)]
+    struct W(Vec<i32>);
+    let w = W(vec![1, 2, 3]);
+    assert_eq!(from_value(to_value(&w).unwrap()), Ok(w));
+
+    let value = |edn: &str| edn.parse::<Value>().unwrap();
+    assert_eq!(from_value(value("(Point)")), Ok(Shape::Point));
+    assert_eq!(
+        from_value::<Value>(value("#{(a) [b]}")),
+        Ok(value("#{(a) [b]}"))
+    );
+    assert_eq!(
+        from_value::<u8>(value("300")).unwrap_err().to_string(),
+        "invalid value: integer `300`, expected u8"
+    );
+    assert_eq!(
+        from_value::<Shape>(value("{:Point nil :Line [1 2]}"))
+            .unwrap_err()
+            .to_string(),
+        "invalid length 2, expected a map with a single key"
+    );
+    assert_eq!(
+        from_value::<Shape>(value("(Circle 1.0 2.0)"))
+            .unwrap_err()
+            .to_string(),
+        "invalid length 2, expected a single value after the variant name"
+    );
+    assert_eq!(
+        from_value::<String>(value(":kw")).unwrap_err().to_string(),
+        "invalid type: keyword, expected a string"
+    );
+}
+
+#[test]
+fn from_value_ref() {
+    use serde_edn::Value;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Route<'a> {
+        method: &'a str,
+        path: &'a str,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Body<'a> {
+        Text(&'a str),
+        Json { raw: &'a str },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Request<'a> {
+        #[serde(borrow)]
+        body: Body<'a>,
+        headers: std::collections::BTreeMap<&'a str, &'a str>,
+    }
+
+    let envelope: Value = r#"{:method "GET"
+                               :path "/users"
+                               :body #http/Text "hi"
+                               :headers {"accept" "text/plain"}}"#
+        .parse()
+        .unwrap();
+
+    // the same envelope, read into several views
+    let route = Route::deserialize(&envelope).unwrap();
+    assert_eq!(
+        route,
+        Route {
+            method: "GET",
+            path: "/users"
+        }
+    );
+    let path = &envelope.as_map().unwrap()[&Value::Keyword("path".into())];
+    match path {
+        Value::String(s) => assert_eq!(route.path.as_ptr(), s.as_ptr()),
+        other => panic!("{:?}", other),
+    }
+
+    let request = Request::deserialize(&envelope).unwrap();
+    assert_eq!(request.body, Body::Text("hi"));
+    assert_eq!(request.headers["accept"], "text/plain");
+
+    let value: Value = r#"[{:Json {:raw "{}"}} (Text "x") :a sym (1 [2])]"#
+        .parse()
+        .unwrap();
+    let items = value.as_vector().unwrap();
+    assert_eq!(Body::deserialize(&items[0]), Ok(Body::Json { raw: "{}" }));
+    assert_eq!(Body::deserialize(&items[1]), Ok(Body::Text("x")));
+    assert_eq!(
+        <&str>::deserialize(&items[2]).unwrap_err().to_string(),
+        "invalid type: keyword, expected a borrowed string"
+    );
+    assert_eq!(
+        <Vec<&str>>::deserialize(&value).unwrap_err().to_string(),
+        "invalid type: map, expected a borrowed string"
+    );
+    assert_eq!(Value::deserialize(&value), Ok(value.clone()));
+    assert_eq!(<(i64, Vec<i64>)>::deserialize(&items[4]), Ok((1, vec![2])));
+
+    #[derive(Debug, PartialEq, Deserialize, serde_derive::Serialize)]
+    struct W(Vec<i32>);
+    let w = W(vec![1, 2, 3]);
+    let value = serde_edn::to_value(&w).unwrap();
+    assert_eq!(W::deserialize(&value), Ok(w));
 }
@@ -0,0 +1,220 @@
+use serde_derive::{Deserialize, Serialize};
+use serde_edn::{from_str, from_value, to_string, to_value, Inst, Tagged, Value};
+
+fn inst(edn: &str) -> Inst {
+    match edn.parse::<Value>() {
+        Ok(Value::Tagged(Tagged::Inst(inst))) => inst,
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn parse_and_print() {
+    let i = inst(r#"#inst "1985-04-12T23:20:50.52Z""#);
+    assert_eq!(i.unix_timestamp(), 482196050);
+    assert_eq!(i.nanosecond(), 520_000_000);
+    assert_eq!(i.offset_minutes(), 0);
+    assert_eq!(i.to_string(), "1985-04-12T23:20:50.52Z");
+
+    // the offset is kept, the instant being the same
+    let j = inst(r#"#inst "1985-04-12T18:20:50.52-05:00""#);
+    assert_eq!(j.unix_timestamp(), i.unix_timestamp());
+    assert_eq!(j.offset_minutes(), -300);
+    assert_eq!(j.to_string(), "1985-04-12T18:20:50.52-05:00");
+    assert_ne!(i, j);
+
+    let cases = &[
+        ("1970", "1970-01-01T00:00:00Z"),
+        ("2000-02", "2000-02-01T00:00:00Z"),
+        ("2000-02-29", "2000-02-29T00:00:00Z"),
+        (
+            "1969-12-31T23:59:59.999999999Z",
+            "1969-12-31T23:59:59.999999999Z",
+        ),
+        (
+            "2024-01-01T00:00:00.1234567891+01:30",
+            "2024-01-01T00:00:00.123456789+01:30",
+        ),
+        ("0001-01-01T00:00", "0001-01-01T00:00:00Z"),
+    ];
+    for (text, printed) in cases {
+        let value: Value = format!("#inst \"{}\"", text).parse().unwrap();
+        assert_eq!(value.to_string(), format!("#inst \"{}\"", printed));
+        assert_eq!(
+            text.parse::<Inst>().map(|i| i.to_string()).as_deref(),
+            Ok(*printed)
+        );
+    }
+    assert_eq!(
+        inst(r#"#inst "1969-12-31T23:59:59.5Z""#).unix_timestamp(),
+        -1
+    );
+}
+
+#[test]
+fn invalid() {
+    for text in &[
+        "85-04-12",
+        "1985-13-01",
+        "1985-02-29",
+        "1985-04-12T24:00",
+        "1985-04-12T23:20:50.Z",
+        "1985-04-12 23:20",
+        "1985-04-12T23:20+0500",
+        "1985-04-12T23:20Zjunk",
+        // leap seconds have no Unix time
+        "1990-12-31T23:59:60Z",
+    ] {
+        assert_eq!(
+            format!("[#inst \"{}\"]", text)
+                .parse::<Value>()
+                .unwrap_err()
+                .to_string(),
+            "invalid #inst timestamp at line 1 column 2",
+            "{}",
+            text
+        );
+        assert!(text.parse::<Inst>().is_err());
+    }
+    assert_eq!(
+        "#inst 1".parse::<Value>().unwrap_err().to_string(),
+        "invalid #inst timestamp at line 1 column 1"
+    );
+}
+
+#[test]
+fn from_unix() {
+    let i = Inst::from_unix(482196050, 520_000_000, -300).unwrap();
+    assert_eq!(i.to_string(), "1985-04-12T18:20:50.52-05:00");
+    assert_eq!(
+        Inst::from_unix(0, 0, -(23 * 60 + 59)).unwrap().to_string(),
+        "1969-12-31T00:01:00-23:59"
+    );
+
+    assert_eq!(Inst::from_unix(0, 1_000_000_000, 0), None);
+    assert_eq!(Inst::from_unix(0, 0, 24 * 60), None);
+    assert_eq!(Inst::from_unix(0, 0, -24 * 60), None);
+    assert_eq!(Inst::from_unix(0, 0, i16::MIN), None);
+    assert_eq!(Inst::from_unix(0, 0, i16::MAX), None);
+
+    // every timestamp prints with a four-digit year and reads back
+    let cases = &[
+        (-62_167_219_200, 0, "0000-01-01T00:00:00Z"),
+        (-62_167_219_200 + 60, 0, "0000-01-01T00:00:00-00:01"),
+        (
+            253_402_300_799,
+            999_999_999,
+            "9999-12-31T23:59:59.999999999Z",
+        ),
+        (253_402_300_799 - 60, 0, "9999-12-31T23:59:59+00:01"),
+    ];
+    for &(secs, nanos, text) in cases {
+        let offset = text.parse::<Inst>().unwrap().offset_minutes();
+        let i = Inst::from_unix(secs, nanos, offset).unwrap();
+        assert_eq!(i.to_string(), text);
+        assert_eq!(text.parse::<Inst>(), Ok(i));
+    }
+    assert_eq!(Inst::from_unix(-62_167_219_201, 0, 0), None);
+    assert_eq!(Inst::from_unix(-62_167_219_200, 0, -1), None);
+    assert_eq!(Inst::from_unix(253_402_300_800, 0, 0), None);
+    assert_eq!(Inst::from_unix(253_402_300_799, 0, 1), None);
+    assert_eq!(Inst::from_unix(i64::MAX, 0, 60), None);
+    assert_eq!(Inst::from_unix(i64::MIN, 0, -60), None);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Event {
+    at: Inst,
+    seen: Option<Inst>,
+}
+
+#[test]
+fn typed_fields() {
+    let text = r#"{:at #inst "2024-03-01T12:00:00.001+02:00" :seen nil}"#;
+    let event = Event {
+        at: "2024-03-01T12:00:00.001+02:00".parse().unwrap(),
+        seen: None,
+    };
+    assert_eq!(from_str::<Event>(text), Ok(event));
+
+    let event = from_str::<Event>(text).unwrap();
+    assert_eq!(to_string(&event).unwrap(), text);
+
+    let value = to_value(&event).unwrap();
+    assert_eq!(value, text.parse().unwrap());
+    assert_eq!(from_value::<Event>(value.clone()), Ok(event));
+    assert_eq!(from_str::<Value>(text), Ok(value));
+
+    assert_eq!(
+        from_str::<Event>(r#"{:at #uuid "x" :seen nil}"#)
+            .unwrap_err()
+            .to_string(),
+        "invalid value: string \"uuid\", expected the tag inst at line 1 column 6"
+    );
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_conversions() {
+    use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+    use serde_edn::InstRangeError;
+    use std::convert::TryFrom;
+
+    let i: Inst = "1985-04-12T18:20:50.52-05:00".parse().unwrap();
+    let dt = DateTime::<FixedOffset>::try_from(i).unwrap();
+    assert_eq!(dt.timestamp(), 482196050);
+    assert_eq!(dt.timestamp_subsec_nanos(), 520_000_000);
+    assert_eq!(dt.offset().local_minus_utc(), -5 * 3600);
+    assert_eq!(Inst::try_from(dt), Ok(i));
+
+    let utc = DateTime::<Utc>::try_from(i).unwrap();
+    assert_eq!(utc.timestamp(), 482196050);
+    assert_eq!(
+        Inst::try_from(utc).unwrap().to_string(),
+        "1985-04-12T23:20:50.52Z"
+    );
+
+    for text in &[
+        "0000-01-01T00:00:00Z",
+        "9999-12-31T23:59:59.999999999+23:59",
+    ] {
+        let i: Inst = text.parse().unwrap();
+        let dt = DateTime::<FixedOffset>::try_from(i).unwrap();
+        assert_eq!(Inst::try_from(dt), Ok(i));
+    }
+
+    let far = NaiveDate::from_ymd_opt(10000, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_utc();
+    assert_eq!(Inst::try_from(far), Err(InstRangeError));
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn time_conversions() {
+    use serde_edn::InstRangeError;
+    use std::convert::TryFrom;
+    use time::OffsetDateTime;
+
+    let i: Inst = "1985-04-12T18:20:50.52-05:00".parse().unwrap();
+    let dt = OffsetDateTime::try_from(i).unwrap();
+    assert_eq!(dt.unix_timestamp(), 482196050);
+    assert_eq!(dt.nanosecond(), 520_000_000);
+    assert_eq!(dt.offset().whole_minutes(), -300);
+    assert_eq!(Inst::try_from(dt), Ok(i));
+
+    for text in &[
+        "0000-01-01T00:00:00Z",
+        "9999-12-31T23:59:59.999999999+23:59",
+    ] {
+        let i: Inst = text.parse().unwrap();
+        let dt = OffsetDateTime::try_from(i).unwrap();
+        assert_eq!(Inst::try_from(dt), Ok(i));
+    }
+
+    // the last second of the year -1
+    let early = OffsetDateTime::from_unix_timestamp(-62_167_219_201).unwrap();
+    assert_eq!(Inst::try_from(early), Err(InstRangeError));
+}
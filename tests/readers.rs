@@ -0,0 +1,206 @@
+use serde::Deserialize;
+use serde_derive::{Deserialize, Serialize};
+use serde_edn::{Deserializer, Error, TagReaders, UnknownTag, Value};
+
+fn read<'de, T>(text: &'de str, readers: &TagReaders) -> Result<T, Error>
+where
+    T: Deserialize<'de>,
+{
+    let mut de = Deserializer::from_str(text).with_tag_readers(readers.clone());
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+fn cents(value: Value) -> Result<Value, String> {
+    match value {
+        Value::Vector(v) => match (v.first(), v.get(1)) {
+            (Some(Value::Integer(units)), Some(Value::String(_))) => {
+                Ok(Value::Integer(units * 100))
+            }
+            _ => Err("expected [units currency]".to_owned()),
+        },
+        _ => Err("expected a vector".to_owned()),
+    }
+}
+
+#[test]
+fn values() {
+    let readers = TagReaders::new().reader("money", cents);
+    assert_eq!(
+        read::<Value>(r#"[#money [3 "EUR"] #other 1]"#, &readers),
+        Ok(Value::Vector(vec![
+            Value::Integer(300),
+            "#other 1".parse().unwrap(),
+        ]))
+    );
+
+    // readers see nested literals already read
+    let readers = readers.reader("ref", |value| match value {
+        Value::Integer(i) => Ok(Value::String(format!("ref-{}", i))),
+        other => Err(format!("unexpected {}", other)),
+    });
+    assert_eq!(
+        read::<Value>(r#"#ref #money [1 "EUR"]"#, &readers),
+        Ok(Value::String("ref-100".to_owned()))
+    );
+
+    assert_eq!(
+        read::<Value>(r#"[1 #money 2]"#, &readers)
+            .unwrap_err()
+            .to_string(),
+        "expected a vector at line 1 column 4"
+    );
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Point {
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Place {
+    name: String,
+    at: Point,
+    price: i64,
+    owner: String,
+}
+
+#[test]
+fn typed_fields() {
+    let readers = TagReaders::new()
+        .reader("money", cents)
+        .reader("ref", |value| match value {
+            Value::Keyword(k) => Ok(Value::String(k)),
+            _ => Err("expected a keyword"),
+        })
+        .typed_reader("geo/point", |value| match value {
+            Value::Vector(v) => match (v.first(), v.get(1)) {
+                (Some(Value::Float(lat)), Some(Value::Float(lon))) => Ok(Point {
+                    lat: lat.into_inner(),
+                    lon: lon.into_inner(),
+                }),
+                _ => Err("expected [lat lon]"),
+            },
+            _ => Err("expected [lat lon]"),
+        });
+
+    let text =
+        r#"{:name "hut" :at #geo/point [45.5 25.25] :price #money [7 "EUR"] :owner #ref :users/1}"#;
+    assert_eq!(
+        read::<Place>(text, &readers),
+        Ok(Place {
+            name: "hut".to_owned(),
+            at: Point {
+                lat: 45.5,
+                lon: 25.25
+            },
+            price: 700,
+            owner: "users/1".to_owned(),
+        })
+    );
+
+    assert_eq!(
+        read::<Place>(
+            r#"{:name "hut" :at #geo/point [1 2] :price 0 :owner "x"}"#,
+            &readers
+        )
+        .unwrap_err()
+        .to_string(),
+        "expected [lat lon] at line 1 column 18"
+    );
+
+    // without readers the literals are not what the fields expect
+    assert!(read::<Place>(text, &TagReaders::new()).is_err());
+}
+
+#[test]
+fn unknown_tags() {
+    let text =
+        r#"[#my/id 7 #inst "1970-01-01T00:00:00Z" #uuid "f81d4fae-7dec-11d0-a765-00a0c91e6bf6"]"#;
+
+    let kept = read::<Vec<Value>>(text, &TagReaders::new()).unwrap();
+    assert_eq!(Value::Vector(kept.clone()), text.parse().unwrap());
+
+    let readers = TagReaders::new().unknown(UnknownTag::Strip);
+    let stripped = read::<Vec<Value>>(text, &readers).unwrap();
+    assert_eq!(stripped[0], Value::Integer(7));
+    assert_eq!(stripped[1..], kept[1..]);
+    assert_eq!(
+        read::<Vec<u8>>("[1 #a #b 2 3]", &readers),
+        Ok(vec![1, 2, 3])
+    );
+
+    let readers = TagReaders::new().unknown(UnknownTag::Error);
+    let err = read::<Value>(text, &readers).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "no reader for the tag #my/id at line 1 column 2"
+    );
+    assert!(err.is_data());
+    assert_eq!(
+        read::<u8>("#my/id 7", &readers).unwrap_err().to_string(),
+        "no reader for the tag #my/id at line 1 column 1"
+    );
+    // byte arrays and registered tags are known
+    let readers = readers.reader("my/id", Ok::<_, String>);
+    assert_eq!(
+        read::<(u8, serde_bytes::ByteBuf)>(r#"(#my/id 7 #base64 "ZWRu")"#, &readers),
+        Ok((7, serde_bytes::ByteBuf::from(b"edn".to_vec())))
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Shape {
+    Circle(u32),
+    Dot,
+}
+
+#[test]
+fn enum_tags() {
+    // tags naming variants are untouched unless a reader is registered for them
+    let readers = TagReaders::new().reader("money", cents);
+    assert_eq!(read::<Shape>("#Circle 2", &readers), Ok(Shape::Circle(2)));
+    assert_eq!(
+        read::<Shape>("#my/shape :Dot", &readers)
+            .unwrap_err()
+            .to_string(),
+        "unknown variant `shape`, expected `Circle` or `Dot` at line 1 column 1"
+    );
+
+    let readers = readers.reader("shape", |value| match value {
+        Value::Integer(r) => Ok(format!("{{:Circle {}}}", r).parse::<Value>().unwrap()),
+        _ => Err("expected a radius"),
+    });
+    assert_eq!(read::<Shape>("#shape 3", &readers), Ok(Shape::Circle(3)));
+
+    let readers = readers.unknown(UnknownTag::Strip);
+    assert_eq!(read::<Shape>("#my/shape :Dot", &readers), Ok(Shape::Dot));
+    assert!(read::<Shape>("#Circle 2", &readers).is_err());
+
+    let readers = readers.unknown(UnknownTag::Error);
+    assert_eq!(
+        read::<Shape>("#Circle 2", &readers)
+            .unwrap_err()
+            .to_string(),
+        "no reader for the tag #Circle at line 1 column 1"
+    );
+    assert_eq!(read::<Shape>("#shape 4", &readers), Ok(Shape::Circle(4)));
+}
+
+#[test]
+fn field_name_tags() {
+    let text = r#"{#my/key :lat 1.5 :lon 2.5}"#;
+    let readers = TagReaders::new().unknown(UnknownTag::Strip);
+    assert_eq!(
+        read::<Point>(text, &readers),
+        Ok(Point { lat: 1.5, lon: 2.5 })
+    );
+
+    let readers = readers.unknown(UnknownTag::Error);
+    assert_eq!(
+        read::<Point>(text, &readers).unwrap_err().to_string(),
+        "no reader for the tag #my/key at line 1 column 2"
+    );
+}
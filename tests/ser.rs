@@ -0,0 +1,540 @@
+use serde_derive::{Deserialize, Serialize};
+use serde_edn::{
+    to_string, to_string_pretty, to_value, to_vec, to_writer, Formatter, PrettyFormatter,
+    Serializer,
+};
+
+use std::collections::{BTreeMap, BTreeSet, LinkedList};
+use std::io;
+
+#[test]
+fn primitives() {
+    assert_eq!(to_string(&()).unwrap(), "nil");
+    assert_eq!(to_string(&true).unwrap(), "true");
+    assert_eq!(to_string(&false).unwrap(), "false");
+    assert_eq!(to_string(&-53_i8).unwrap(), "-53");
+    assert_eq!(to_string(&u64::MAX).unwrap(), "18446744073709551615");
+    assert_eq!(
+        to_string(&i128::MIN).unwrap(),
+        "-170141183460469231731687303715884105728"
+    );
+    assert_eq!(
+        to_string(&u128::MAX).unwrap(),
+        "340282366920938463463374607431768211455"
+    );
+    assert_eq!(to_string(&Option::<u32>::None).unwrap(), "nil");
+    assert_eq!(to_string(&Some(3)).unwrap(), "3");
+}
+
+#[test]
+fn floats() {
+    assert_eq!(to_string(&0.5_f64).unwrap(), "0.5");
+    assert_eq!(to_string(&13.0_f32).unwrap(), "13.0");
+    assert_eq!(to_string(&0.3_f32).unwrap(), "0.3");
+    assert_eq!(to_string(&f32::NEG_INFINITY).unwrap(), "##-Inf");
+    assert_eq!(to_string(&1e300_f64).unwrap(), "1e300");
+    assert_eq!(to_string(&f64::NAN).unwrap(), "##NaN");
+    assert_eq!(to_string(&f64::INFINITY).unwrap(), "##Inf");
+    assert_eq!(to_string(&f64::NEG_INFINITY).unwrap(), "##-Inf");
+}
+
+#[test]
+fn chars() {
+    assert_eq!(to_string(&'c').unwrap(), r#"\c"#);
+    assert_eq!(to_string(&'\n').unwrap(), r#"\newline"#);
+    assert_eq!(to_string(&'\r').unwrap(), r#"\return"#);
+    assert_eq!(to_string(&' ').unwrap(), r#"\space"#);
+    assert_eq!(to_string(&'\t').unwrap(), r#"\tab"#);
+    assert_eq!(to_string(&'\u{7}').unwrap(), r#"\u0007"#);
+}
+
+#[test]
+fn strings() {
+    assert_eq!(to_string("astring").unwrap(), r#""astring""#);
+    assert_eq!(
+        to_string("a\tb\rc\nd\\e\"f").unwrap(),
+        r#""a\tb\rc\nd\\e\"f""#
+    );
+    assert_eq!(to_string("ü\u{1}").unwrap(), r#""ü\u0001""#);
+}
+
+#[test]
+fn sequences() {
+    assert_eq!(to_string(&vec![3, 4, 6]).unwrap(), "[3 4 6]");
+    assert_eq!(to_string(&Vec::<i32>::new()).unwrap(), "[]");
+    assert_eq!(to_string(&(10, "abcd")).unwrap(), r#"(10 "abcd")"#);
+
+    // serde does not tell sets and lists apart from other sequences
+    let list: LinkedList<_> = vec![1, 2].into_iter().collect();
+    assert_eq!(to_string(&list).unwrap(), "[1 2]");
+
+    let set: BTreeSet<_> = vec![9, 4, 3].into_iter().collect();
+    assert_eq!(to_string(&set).unwrap(), "[3 4 9]");
+}
+
+#[test]
+fn set_and_list_helpers() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct S {
+        #[serde(with = "serde_edn::set")]
+        set: BTreeSet<i32>,
+        #[serde(with = "serde_edn::list")]
+        list: LinkedList<i32>,
+        #[serde(with = "serde_edn::list")]
+        vec: Vec<i32>,
+    }
+
+    let s = S {
+        set: vec![9, 4, 3].into_iter().collect(),
+        list: vec![1, 2].into_iter().collect(),
+        vec: vec![],
+    };
+    let text = "{:set #{3 4 9} :list (1 2) :vec ()}";
+    assert_eq!(to_string(&s).unwrap(), text);
+    assert_eq!(serde_edn::from_str::<S>(text), Ok(s));
+
+    let s = serde_edn::from_str::<S>(text).unwrap();
+    let value = to_value(&s).unwrap();
+    assert_eq!(value, text.parse().unwrap());
+    assert_eq!(serde_edn::from_value::<S>(value), Ok(s));
+}
+
+#[test]
+fn unit_struct() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Unit;
+
+    assert_eq!(to_string(&Unit).unwrap(), "nil");
+    assert_eq!(to_string(&vec![Unit]).unwrap(), "[nil]");
+    assert_eq!(serde_edn::from_str::<Unit>("nil"), Ok(Unit));
+    assert_eq!(to_value(&Unit), Ok(serde_edn::Value::Nil));
+    assert_eq!(
+        serde_edn::from_value::<Unit>(to_value(&Unit).unwrap()),
+        Ok(Unit)
+    );
+}
+
+#[test]
+fn map() {
+    let mut m = BTreeMap::new();
+    m.insert(5, "abc");
+    m.insert(8, "def");
+    assert_eq!(to_string(&m).unwrap(), r#"{5 "abc" 8 "def"}"#);
+
+    let mut m = BTreeMap::new();
+    m.insert((1, 2), vec![true]);
+    assert_eq!(to_string(&m).unwrap(), "{(1 2) [true]}");
+}
+
+#[test]
+fn structs() {
+    #[derive(Serialize)]
+    struct YewNit;
+
+    #[derive(Serialize)]
+    struct Newtype(i32);
+
+    #[derive(Serialize)]
+    struct Tup(i32, String);
+
+    #[derive(Serialize)]
+    struct S {
+        a: u32,
+        b: String,
+        c: Option<Tup>,
+    }
+
+    assert_eq!(to_string(&YewNit).unwrap(), "nil");
+    assert_eq!(to_string(&Newtype(10)).unwrap(), "10");
+    assert_eq!(
+        to_string(&Tup(10, "abcd".into())).unwrap(),
+        r#"(10 "abcd")"#
+    );
+
+    let s = S {
+        a: 74,
+        b: "abc".into(),
+        c: Some(Tup(1, "x".into())),
+    };
+    assert_eq!(to_string(&s).unwrap(), r#"{:a 74 :b "abc" :c (1 "x")}"#);
+}
+
+#[test]
+fn keyword_names() {
+    #[derive(Serialize)]
+    struct Spaced {
+        #[serde(rename = "a b")]
+        a: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Empty {
+        #[serde(rename = "")]
+        a: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Namespaced {
+        #[serde(rename = "user/id")]
+        id: u8,
+    }
+
+    #[derive(Serialize)]
+    enum E {
+        #[serde(rename = "1st")]
+        First,
+        #[serde(rename = "x-y")]
+        Second(u8),
+    }
+
+    assert_eq!(
+        to_string(&Spaced { a: 1 }).unwrap_err().to_string(),
+        "`a b` cannot be written as a keyword"
+    );
+    assert_eq!(
+        to_string(&Empty { a: 1 }).unwrap_err().to_string(),
+        "`` cannot be written as a keyword"
+    );
+    assert_eq!(to_string(&Namespaced { id: 1 }).unwrap(), "{:user/id 1}");
+    assert_eq!(
+        to_string(&E::First).unwrap_err().to_string(),
+        "`1st` cannot be written as a keyword"
+    );
+    assert_eq!(to_string(&E::Second(1)).unwrap(), "{:x-y 1}");
+    assert!(to_string(&serde_edn::Value::Keyword("a b".into())).is_err());
+}
+
+#[test]
+fn newtype_round_trip() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct W(Vec<i32>);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct T((i32, i32));
+
+    let w = W(vec![1, 2, 3]);
+    let text = to_string(&w).unwrap();
+    assert_eq!(text, "[1 2 3]");
+    assert_eq!(serde_edn::from_str::<W>(&text), Ok(w));
+
+    let t = T((1, 2));
+    let text = to_string(&t).unwrap();
+    assert_eq!(text, "(1 2)");
+    assert_eq!(serde_edn::from_str::<T>(&text), Ok(t));
+}
+
+#[test]
+fn enums() {
+    #[derive(Serialize)]
+    enum E {
+        Unit,
+        Newtype(u8),
+        Tuple(u8, u8),
+        Struct { r: u8 },
+    }
+
+    assert_eq!(to_string(&E::Unit).unwrap(), ":Unit");
+    assert_eq!(to_string(&E::Newtype(1)).unwrap(), "{:Newtype 1}");
+    assert_eq!(to_string(&E::Tuple(1, 2)).unwrap(), "{:Tuple (1 2)}");
+    assert_eq!(to_string(&E::Struct { r: 1 }).unwrap(), "{:Struct {:r 1}}");
+    assert_eq!(
+        to_string(&vec![E::Unit, E::Newtype(3)]).unwrap(),
+        "[:Unit {:Newtype 3}]"
+    );
+}
+
+#[test]
+fn writer_and_vec() {
+    let mut out = Vec::new();
+    to_writer(&mut out, &vec![1, 2]).unwrap();
+    assert_eq!(out, b"[1 2]");
+
+    assert_eq!(to_vec(&"x").unwrap(), b"\"x\"");
+}
+
+#[derive(Serialize)]
+struct Config {
+    name: String,
+    keywords: Vec<String>,
+    version: (u32, u32, u32),
+}
+
+fn config() -> Config {
+    Config {
+        name: "serde_edn".into(),
+        keywords: vec!["edn".into(), "serde".into(), "clojure".into()],
+        version: (0, 1, 0),
+    }
+}
+
+#[test]
+fn pretty_fits_on_one_line() {
+    assert_eq!(
+        to_string_pretty(&config()).unwrap(),
+        r#"{:name "serde_edn" :keywords ["edn" "serde" "clojure"] :version (0 1 0)}"#
+    );
+    assert_eq!(to_string_pretty(&Vec::<u8>::new()).unwrap(), "[]");
+    assert_eq!(to_string_pretty(&"x").unwrap(), r#""x""#);
+}
+
+#[test]
+fn pretty_breaks_long_collections() {
+    let out = formatted(&config(), PrettyFormatter::new().with_width(40));
+    assert_eq!(
+        out,
+        r#"{:name "serde_edn"
+ :keywords ["edn" "serde" "clojure"]
+ :version (0 1 0)}"#
+    );
+
+    let out = formatted(&config(), PrettyFormatter::new().with_width(20));
+    assert_eq!(
+        out,
+        r#"{:name "serde_edn"
+ :keywords ["edn"
+            "serde"
+            "clojure"]
+ :version (0 1 0)}"#
+    );
+}
+
+#[test]
+fn pretty_indent() {
+    let out = formatted(
+        &vec![vec![1, 2, 3], vec![4, 5, 6]],
+        PrettyFormatter::new().with_width(10).with_indent(2),
+    );
+    assert_eq!(
+        out,
+        "[[1 2 3]
+  [4 5 6]]"
+    );
+}
+
+#[test]
+fn pretty_enum_variants() {
+    #[derive(Serialize)]
+    enum Shape {
+        Circle { radius: u32, center: (i32, i32) },
+    }
+
+    let out = formatted(
+        &Shape::Circle {
+            radius: 10,
+            center: (-5, 5),
+        },
+        PrettyFormatter::new().with_width(24),
+    );
+    assert_eq!(
+        out,
+        "{:Circle {:radius 10
+          :center (-5 5)}}"
+    );
+}
+
+#[test]
+fn pretty_aligned_map_values() {
+    let mut m = BTreeMap::new();
+    m.insert("a", 1);
+    m.insert("bbb", 2);
+    m.insert("cc", 3);
+
+    let out = formatted(
+        &m,
+        PrettyFormatter::new()
+            .with_width(10)
+            .with_aligned_map_values(),
+    );
+    assert_eq!(
+        out,
+        r#"{"a"   1
+ "bbb" 2
+ "cc"  3}"#
+    );
+}
+
+/// Separates map entries with commas, as `pr-str` does
+struct CommaFormatter;
+
+impl Formatter for CommaFormatter {
+    fn begin_map_key<W>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+}
+
+/// Highlights keywords and strings with ANSI colors
+struct ColorFormatter;
+
+impl Formatter for ColorFormatter {
+    fn write_keyword<W>(&mut self, writer: &mut W, name: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        write!(writer, "\x1b[35m:{}\x1b[0m", name)
+    }
+
+    fn begin_string<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"\x1b[32m\"")
+    }
+
+    fn end_string<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"\"\x1b[0m")
+    }
+}
+
+fn formatted<T: serde::Serialize, F: Formatter>(value: &T, formatter: F) -> String {
+    let mut ser = Serializer::with_formatter(Vec::new(), formatter);
+    value.serialize(&mut ser).unwrap();
+    String::from_utf8(ser.into_inner()).unwrap()
+}
+
+#[test]
+fn custom_formatters() {
+    assert_eq!(
+        formatted(&config(), CommaFormatter),
+        r#"{:name "serde_edn", :keywords ["edn" "serde" "clojure"], :version (0 1 0)}"#
+    );
+
+    #[derive(Serialize)]
+    struct S {
+        k: &'static str,
+    }
+    assert_eq!(
+        formatted(&S { k: "a\"b" }, ColorFormatter),
+        "{\x1b[35m:k\x1b[0m \x1b[32m\"a\\\"b\"\x1b[0m}"
+    );
+}
+
+#[test]
+fn value_shapes() {
+    use maplit::btreemap;
+    use serde_edn::{to_value, Value};
+
+    #[derive(Serialize)]
+    enum E {
+        Unit,
+        Tuple(u8, u8),
+        Struct { r: u8 },
+    }
+
+    #[derive(Serialize)]
+    struct S {
+        name: &'static str,
+        tags: Vec<E>,
+        pair: (char, Option<bool>),
+    }
+
+    let s = S {
+        name: "x",
+        tags: vec![E::Unit, E::Tuple(1, 2), E::Struct { r: 3 }],
+        pair: ('c', None),
+    };
+
+    let kw = |k: &str| Value::Keyword(k.into());
+    let expected = Value::Map(btreemap! {
+        kw("name") => Value::string("x"),
+        kw("tags") => Value::Vector(vec![
+            kw("Unit"),
+            Value::Map(btreemap! {
+                kw("Tuple") => Value::List(vec![Value::integer(1), Value::integer(2)]),
+            }),
+            Value::Map(btreemap! {
+                kw("Struct") => Value::Map(btreemap! { kw("r") => Value::integer(3) }),
+            }),
+        ]),
+        kw("pair") => Value::List(vec![Value::Char('c'), Value::Nil]),
+    });
+
+    assert_eq!(to_value(&s), Ok(expected));
+    assert_eq!(to_value(&u64::MAX), Ok(Value::Integer(u64::MAX.into())));
+    assert_eq!(to_value(&i128::MIN), Ok(Value::Integer(i128::MIN)));
+    assert_eq!(to_value(&u128::MAX), Ok(Value::U128(u128::MAX)));
+    assert_eq!(
+        serde_edn::from_value::<u128>(to_value(&u128::MAX).unwrap()),
+        Ok(u128::MAX)
+    );
+    assert_eq!(
+        to_value(&2_u128.pow(100)),
+        Ok(Value::Integer(2_i128.pow(100)))
+    );
+    assert_eq!(
+        Value::U128(u128::MAX).to_string().parse::<Value>(),
+        Ok(Value::U128(u128::MAX))
+    );
+}
+
+fn sample_value() -> serde_edn::Value {
+    use maplit::{btreemap, btreeset};
+    use serde_edn::{Tagged, Value};
+
+    let sym = |s: &str| match Value::symbol(s) {
+        Value::Symbol(s) => s,
+        _ => unreachable!(),
+    };
+
+    Value::List(vec![
+        Value::symbol("ns/apply"),
+        Value::Map(btreemap! {
+            Value::Keyword("k1".into()) => Value::Set(btreeset! {Value::integer(1), Value::Nil}),
+            Value::Keyword("k2".into()) => Value::Vector(vec![Value::Char('\n'), Value::float(0.5)]),
+        }),
+        Value::Tagged(Tagged::new(sym("my/tag"), Value::string("x"))),
+        Value::Tagged(Tagged::UUID(0xf81d4fae_7dec_11d0_a765_00a0c91e6bf6)),
+        Value::Tagged(Tagged::new(sym("t"), Value::Vector(vec![]))),
+    ])
+}
+
+#[test]
+fn value_to_string() {
+    assert_eq!(
+        to_string(&sample_value()).unwrap(),
+        r#"(ns/apply {:k1 #{nil 1} :k2 [\newline 0.5]} #my/tag "x" #uuid "f81d4fae-7dec-11d0-a765-00a0c91e6bf6" #t [])"#
+    );
+}
+
+#[test]
+fn value_pretty() {
+    let out = formatted(&sample_value(), PrettyFormatter::new().with_width(40));
+    assert_eq!(
+        out,
+        r#"(ns/apply
+ {:k1 #{nil 1} :k2 [\newline 0.5]}
+ #my/tag "x"
+ #uuid "f81d4fae-7dec-11d0-a765-00a0c91e6bf6"
+ #t [])"#
+    );
+}
+
+#[test]
+fn value_inside_struct() {
+    #[derive(Serialize)]
+    struct Envelope {
+        id: u32,
+        body: serde_edn::Value,
+    }
+
+    let env = Envelope {
+        id: 7,
+        body: serde_edn::Value::Keyword("ok".into()),
+    };
+    assert_eq!(to_string(&env).unwrap(), "{:id 7 :body :ok}");
+}
+
+#[test]
+fn value_to_value() {
+    let value = sample_value();
+    assert_eq!(serde_edn::to_value(&value), Ok(value));
+}
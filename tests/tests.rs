@@ -1,6 +1,10 @@
 use serde_edn::from_str;
 use serde_edn::{Error, Value};
 
+fn err<T: std::fmt::Debug>(result: Result<T, Error>) -> String {
+    result.unwrap_err().to_string()
+}
+
 #[test]
 fn nil() {
     let edn = "nil";
@@ -28,7 +32,10 @@ fn string_basic() {
 #[test]
 fn string_eof() {
     let edn = r#""randomda"#;
-    assert_eq!(Err(Error::Eof), from_str::<Value>(edn));
+    assert_eq!(
+        "EOF while parsing a string at line 1 column 10",
+        err(from_str::<Value>(edn))
+    );
 }
 
 #[test]
@@ -60,9 +67,8 @@ fn char_sequences() {
     let edn = r#"\tab"#;
     assert_eq!(Ok(Value::Char('\t')), from_str::<Value>(edn));
 
-    // FIXME: part of edn but not implemented by the parser we use
-    //let edn = r#"\u1234"#;
-    //assert_eq!(Ok(Value::Char('\u{1234}')), from_str::<Value>(edn));
+    let edn = r#"\u1234"#;
+    assert_eq!(Ok(Value::Char('\u{1234}')), from_str::<Value>(edn));
 }
 
 #[test]
@@ -79,9 +85,19 @@ fn integer_fixed() {
     assert_eq!(from_str("-0"), expected);
     assert_eq!(from_str("+0"), expected);
 
-    // FIXME: edn specifies integers may not start with 0, but the parser accepts them
-    //let expected = Err(Error::Bad);
-    //assert_eq!(from_str::<Value>("04"), expected);
+    // beyond i64, as sent for JVM longs read as unsigned
+    let big = "18446744073709551615";
+    assert_eq!(from_str(big), Ok(Value::Integer(u64::MAX.into())));
+    assert_eq!(big.parse::<Value>(), Ok(Value::Integer(u64::MAX.into())));
+    assert_eq!(big.parse::<Value>().unwrap().to_string(), big);
+    assert_eq!(
+        "-170141183460469231731687303715884105728".parse::<Value>(),
+        Ok(Value::Integer(i128::MIN))
+    );
+
+    let expected = "invalid number at line 1 column 1";
+    assert_eq!(err(from_str::<Value>("04")), expected);
+    assert_eq!(err(from_str::<Value>("-04")), expected);
 }
 
 #[test]
@@ -108,5 +124,197 @@ fn list() {
         Value::float(0.3),
         Value::string("defxy"),
     ]));
-    assert_eq!(expected, from_str(r#"(1 .3 "defxy")"#));
+    assert_eq!(expected, from_str(r#"(1 0.3 "defxy")"#));
+
+    // a float must have digits before the decimal point
+    assert_eq!(
+        "invalid symbol at line 1 column 4",
+        err(from_str::<Value>(r#"(1 .3 "defxy")"#))
+    );
+}
+
+#[test]
+fn display() {
+    let edn = r#"(apply ns/f {:k1 #{\newline 1.5} :k2 [nil true "a\"b"]} #my/tag [])"#;
+    let value: Value = edn.parse().unwrap();
+    assert_eq!(value.to_string(), edn);
+
+    assert_eq!(Value::Keyword("kw".into()).to_string(), ":kw");
+    assert_eq!(Value::Char(' ').to_string(), r#"\space"#);
+    assert_eq!(Value::string("tab\there").to_string(), r#""tab\there""#);
+}
+
+#[test]
+fn display_alternate_is_pretty() {
+    let value: Value = r#"{:name "serde_edn" :keywords ["edn" "serde" "clojure"] :description "edn support for serde"}"#
+        .parse()
+        .unwrap();
+    assert_eq!(
+        format!("{:#}", value),
+        r#"{:description "edn support for serde"
+ :keywords ["edn" "serde" "clojure"]
+ :name "serde_edn"}"#
+    );
+}
+
+#[test]
+fn from_str_keeps_edn_constructs() {
+    use ordered_float::OrderedFloat as OF;
+
+    assert_eq!(
+        "[1 2]".parse::<Value>(),
+        Ok(Value::Vector(vec![Value::integer(1), Value::integer(2)]))
+    );
+    assert_eq!(
+        "#{:a}".parse::<Value>(),
+        Ok(Value::Set(
+            vec![Value::Keyword("a".into())].into_iter().collect()
+        ))
+    );
+    assert_eq!("sym".parse::<Value>(), Ok(Value::symbol("sym")));
+    assert_eq!("2.5".parse::<Value>(), Ok(Value::Float(OF(2.5))));
+    assert!("[1 2".parse::<Value>().is_err());
+    assert_eq!(
+        err("[1] 2".parse::<Value>()),
+        "trailing characters at line 1 column 5"
+    );
+}
+
+#[test]
+fn deserialize_keeps_edn_constructs() {
+    let edn = r#"[nil true "s" \c sym :kw 1 2.5 (1 [2]) {:k #{b :a}} #my/tag (x)]"#;
+    let value = from_str::<Value>(edn).unwrap();
+    assert_eq!(Ok(value.clone()), edn.parse::<Value>());
+    assert_eq!(value.to_string(), edn);
+
+    #[derive(Debug, PartialEq, serde_derive::Deserialize)]
+    struct Doc {
+        meta: Value,
+        body: Vec<Value>,
+    }
+
+    assert_eq!(
+        from_str::<Doc>("{:meta #{:a} :body [(f x) #t :y]}"),
+        Ok(Doc {
+            meta: "#{:a}".parse().unwrap(),
+            body: vec!["(f x)".parse().unwrap(), "#t :y".parse().unwrap()],
+        })
+    );
+
+    // the checks of `Value::from_str` apply
+    assert_eq!(
+        err(from_str::<Doc>("{:meta #{1 1} :body []}")),
+        "duplicate set element at line 1 column 12"
+    );
+}
+
+#[test]
+fn float_exponent_and_special() {
+    use ordered_float::OrderedFloat as OF;
+
+    assert_eq!(from_str("1e3"), Ok(Value::Float(OF(1000.0))));
+    assert_eq!(from_str("-2.5E-1"), Ok(Value::Float(OF(-0.25))));
+    // with the `bignum` feature these are `Value::BigDecimal` and `Value::BigInt`
+    #[cfg(not(feature = "bignum"))]
+    {
+        assert_eq!(from_str("3M"), Ok(Value::Float(OF(3.0))));
+        assert_eq!(from_str("7N"), Ok(Value::Integer(7)));
+    }
+    assert_eq!(from_str("##Inf"), Ok(Value::Float(OF(f64::INFINITY))));
+    assert_eq!(from_str("##-Inf"), Ok(Value::Float(OF(f64::NEG_INFINITY))));
+    assert!(from_str::<f64>("##NaN").unwrap().is_nan());
+
+    assert!(from_str::<Value>("1.5N").is_err());
+    assert!(from_str::<Value>("1e").is_err());
+    assert!(from_str::<Value>("1.2.3").is_err());
+}
+
+#[test]
+fn whitespace_comments_and_discard() {
+    let edn = "[1, 2 ; two\n #_ 3 #_(4 [5]) 6]";
+    assert_eq!(
+        edn.parse::<Value>(),
+        Ok(Value::Vector(vec![
+            Value::integer(1),
+            Value::integer(2),
+            Value::integer(6),
+        ]))
+    );
+}
+
+#[test]
+fn symbols_and_keywords() {
+    assert_eq!("ns/name".parse::<Value>(), Ok(Value::symbol("ns/name")));
+    assert_eq!("/".parse::<Value>(), Ok(Value::symbol("/")));
+    assert_eq!("-x".parse::<Value>(), Ok(Value::symbol("-x")));
+    assert_eq!("a.b*c?".parse::<Value>(), Ok(Value::symbol("a.b*c?")));
+    assert_eq!(
+        ":ns/kw".parse::<Value>(),
+        Ok(Value::Keyword("ns/kw".into()))
+    );
+
+    for invalid in &["ns/", "a/b/c"] {
+        assert_eq!(
+            err(invalid.parse::<Value>()),
+            "invalid symbol at line 1 column 1"
+        );
+    }
+    for invalid in &[":", "::kw"] {
+        assert_eq!(
+            err(invalid.parse::<Value>()),
+            "invalid keyword at line 1 column 1"
+        );
+    }
+}
+
+#[test]
+fn tagged() {
+    let value = "#my/tag {:a 1}".parse::<Value>().unwrap();
+    let tagged = value.as_tagged().unwrap();
+    assert_eq!(tagged.tag(), "my/tag");
+
+    assert_eq!(
+        err("#1tag 1".parse::<Value>()),
+        "invalid tag at line 1 column 1"
+    );
+    assert_eq!(
+        err("#my/tag".parse::<Value>()),
+        "EOF while parsing a value at line 1 column 8"
+    );
+}
+
+#[test]
+fn malformed_collections() {
+    let cases = &[
+        ("[1 2)", "unexpected closing delimiter at line 1 column 5"),
+        ("(1 2", "EOF while parsing a list at line 1 column 5"),
+        ("{:a}", "map key without a value at line 1 column 4"),
+        ("{:a 1 :a 2}", "duplicate map key at line 1 column 7"),
+        ("#{1 1}", "duplicate set element at line 1 column 5"),
+        ("]", "unexpected closing delimiter at line 1 column 1"),
+        ("@", "unexpected character at line 1 column 1"),
+    ];
+    for (edn, expected) in cases {
+        assert_eq!(&err(edn.parse::<Value>()), expected);
+    }
+}
+
+#[test]
+fn error_classify() {
+    use serde_edn::Category;
+
+    assert_eq!(err_category("(1 2"), Category::Eof);
+    assert_eq!(err_category("(1 2]"), Category::Syntax);
+    assert_eq!(err_category("04"), Category::Syntax);
+
+    let data = from_str::<Vec<bool>>("[true nil]").unwrap_err();
+    assert_eq!(data.classify(), Category::Data);
+    assert_eq!(
+        data.to_string(),
+        "invalid type: nil, expected a boolean at line 1 column 7"
+    );
+}
+
+fn err_category(edn: &str) -> serde_edn::Category {
+    edn.parse::<Value>().unwrap_err().classify()
 }
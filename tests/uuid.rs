@@ -0,0 +1,105 @@
+use serde_edn::{Tagged, Value};
+
+const ID: &str = "f81d4fae-7dec-11d0-a765-00a0c91e6bf6";
+
+#[test]
+fn parse_and_print() {
+    let value: Value = format!("#uuid \"{}\"", ID).parse().unwrap();
+    assert_eq!(
+        value,
+        Value::Tagged(Tagged::UUID(0xf81d4fae_7dec_11d0_a765_00a0c91e6bf6))
+    );
+    assert_eq!(value.to_string(), format!("#uuid \"{}\"", ID));
+
+    // printed back in lowercase
+    let value: Value = format!("#uuid \"{}\"", ID.to_uppercase()).parse().unwrap();
+    assert_eq!(value.to_string(), format!("#uuid \"{}\"", ID));
+
+    let value: Value = r#"#uuid "00000000-0000-0000-0000-000000000001""#.parse().unwrap();
+    assert_eq!(value, Value::Tagged(Tagged::UUID(1)));
+    assert_eq!(
+        value.to_string(),
+        r#"#uuid "00000000-0000-0000-0000-000000000001""#
+    );
+}
+
+#[test]
+fn invalid() {
+    for text in &[
+        "",
+        "f81d4fae7dec11d0a76500a0c91e6bf6",
+        "f81d4fae-7dec-11d0-a765-00a0c91e6bf",
+        "f81d4fae-7dec-11d0-a765-00a0c91e6bf6a",
+        "f81d4fae-7dec-11d0a-765-00a0c91e6bf6",
+        "g81d4fae-7dec-11d0-a765-00a0c91e6bf6",
+        "+81d4fae-7dec-11d0-a765-00a0c91e6bf6",
+        "{f81d4fae-7dec-11d0-a765-00a0c91e6bf}",
+    ] {
+        assert_eq!(
+            format!("[#uuid \"{}\"]", text)
+                .parse::<Value>()
+                .unwrap_err()
+                .to_string(),
+            "invalid #uuid at line 1 column 2",
+            "{}",
+            text
+        );
+    }
+    assert_eq!(
+        "#uuid 1".parse::<Value>().unwrap_err().to_string(),
+        "invalid #uuid at line 1 column 1"
+    );
+}
+
+#[cfg(feature = "uuid")]
+mod typed {
+    use super::ID;
+    use serde_derive::{Deserialize, Serialize};
+    use serde_edn::{from_str, from_value, to_string, to_value, Tagged, Value};
+    use uuid::Uuid;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entity {
+        #[serde(with = "serde_edn::uuid")]
+        id: Uuid,
+        name: String,
+    }
+
+    #[test]
+    fn fields() {
+        let text = format!("{{:id #uuid \"{}\" :name \"a\"}}", ID);
+        let entity = Entity {
+            id: Uuid::parse_str(ID).unwrap(),
+            name: "a".to_owned(),
+        };
+        assert_eq!(from_str::<Entity>(&text), Ok(entity));
+
+        let entity = from_str::<Entity>(&text).unwrap();
+        assert_eq!(to_string(&entity).unwrap(), text);
+
+        let value = to_value(&entity).unwrap();
+        assert_eq!(value, text.parse().unwrap());
+        assert_eq!(
+            Value::Tagged(Tagged::from(entity.id)).to_string(),
+            format!("#uuid \"{}\"", ID)
+        );
+        assert_eq!(from_value::<Entity>(value.clone()), Ok(entity));
+
+        // plain strings are accepted too
+        let plain = format!("{{:id \"{}\" :name \"a\"}}", ID);
+        assert_eq!(from_str::<Entity>(&plain), from_str::<Entity>(&text));
+
+        assert_eq!(
+            from_str::<Entity>(r#"{:id #inst "2024-01-01" :name "a"}"#)
+                .unwrap_err()
+                .to_string(),
+            "invalid value: string \"inst\", expected the tag uuid at line 1 column 6"
+        );
+        assert_eq!(
+            from_str::<Entity>(r#"{:id "f81d4fae" :name "a"}"#)
+                .unwrap_err()
+                .to_string(),
+            "invalid value: string \"f81d4fae\", expected a #uuid literal at line 1 column 6"
+        );
+    }
+}